@@ -1,9 +1,12 @@
 use arc_camera::CameraPlugin;
+use arc_canvas::{
+    CanvasPlugin,
+    config::{CanvasConfig, CanvasFormat},
+};
 use arc_fps::FpsPlugin;
 use arc_langton::LangtonPlugin;
 use arc_random::RandomPlugin;
 use bevy::{math::U8Vec2, prelude::*};
-use bevy_canvas_2d::prelude::*;
 use bevy_egui::EguiPlugin;
 
 const BOARD_SIZE: UVec2 = UVec2::new(1024 * 4, 1024 * 4);
@@ -20,7 +23,8 @@ impl Plugin for ArcPlugin {
                     canvas_size: BOARD_SIZE,
                     num_chunks: U8Vec2::new(1, 1),
                     ..default()
-                },
+                }
+                .with_format(CanvasFormat::Indexed),
             })
             .add_plugins((CameraPlugin, FpsPlugin, LangtonPlugin, RandomPlugin));
     }
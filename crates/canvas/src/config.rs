@@ -1,11 +1,23 @@
 use bevy::{math::U8Vec2, prelude::*};
 
+/// How the canvas stores its pixels, on the CPU and on the GPU.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum CanvasFormat {
+    /// One packed RGBA8 value per pixel, drawn with `DrawPixel`, `DrawRect` and `DrawSpan`.
+    #[default]
+    Rgba,
+    /// One byte per pixel, drawn with `DrawIndex` and `DrawIndexRect`, and coloured from `CanvasPalette` by the GPU.
+    /// RGBA draw messages are ignored, and the canvas starts cleared to index 0.
+    Indexed,
+}
+
 #[derive(Resource, Clone)]
 pub struct CanvasConfig {
     pub clear_colour: [u8; 4],
     pub canvas_z_index: f32,
     pub canvas_size: UVec2,
     pub num_chunks: U8Vec2,
+    pub format: CanvasFormat,
 }
 
 impl CanvasConfig {
@@ -14,16 +26,22 @@ impl CanvasConfig {
         debug_assert!(canvas_size.y > 0);
         debug_assert!(chunks.x > 0);
         debug_assert!(chunks.y > 0);
-        debug_assert!(canvas_size.x % chunks.x as u32 == 0);
-        debug_assert!(canvas_size.y % chunks.y as u32 == 0);
+        debug_assert!(canvas_size.x.is_multiple_of(chunks.x as u32));
+        debug_assert!(canvas_size.y.is_multiple_of(chunks.y as u32));
         Self {
             clear_colour,
             canvas_z_index,
             canvas_size,
             num_chunks: chunks,
+            format: CanvasFormat::Rgba,
         }
     }
 
+    pub fn with_format(mut self, format: CanvasFormat) -> Self {
+        self.format = format;
+        self
+    }
+
     #[inline]
     pub fn clear_colour(&self) -> &[u8; 4] {
         &self.clear_colour
@@ -53,6 +71,11 @@ impl CanvasConfig {
         (chunk_size.x as usize) * (chunk_size.y as usize)
     }
 
+    #[inline]
+    pub fn format(&self) -> CanvasFormat {
+        self.format
+    }
+
     #[inline]
     pub fn num_chunks(&self) -> U8Vec2 {
         self.num_chunks
@@ -71,6 +94,7 @@ impl Default for CanvasConfig {
             canvas_z_index: 0.0,
             canvas_size: UVec2::new(1024, 512),
             num_chunks: U8Vec2::new(8, 4),
+            format: CanvasFormat::Rgba,
         }
     }
 }
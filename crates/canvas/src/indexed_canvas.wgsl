@@ -0,0 +1,12 @@
+#import bevy_sprite::mesh2d_vertex_output::VertexOutput
+
+@group(#{MATERIAL_BIND_GROUP}) @binding(0) var indices: texture_2d<u32>;
+@group(#{MATERIAL_BIND_GROUP}) @binding(1) var palette: texture_2d<f32>;
+
+@fragment
+fn fragment(in: VertexOutput) -> @location(0) vec4<f32> {
+    let size = textureDimensions(indices, 0);
+    let coord = clamp(vec2<u32>(floor(in.uv * vec2<f32>(size))), vec2<u32>(0u), size - 1u);
+    let index = textureLoad(indices, coord, 0).r;
+    return textureLoad(palette, vec2<u32>(index, 0u), 0);
+}
@@ -0,0 +1,60 @@
+use bevy::{
+    asset::embedded_asset,
+    prelude::*,
+    render::{Render, RenderApp, RenderSystems, extract_resource::ExtractResourcePlugin},
+    sprite_render::Material2dPlugin,
+};
+
+mod components;
+pub mod config;
+pub mod material;
+pub mod messages;
+pub mod palette;
+mod resources;
+mod systems;
+mod types;
+
+use config::*;
+use material::*;
+use messages::*;
+use palette::*;
+use resources::*;
+use systems::*;
+
+pub struct CanvasPlugin {
+    pub config: CanvasConfig,
+}
+
+impl Plugin for CanvasPlugin {
+    fn build(&self, app: &mut App) {
+        // Plugins
+        app.add_plugins(ExtractResourcePlugin::<CanvasUploadOps>::default());
+
+        // Messages
+        app.add_message::<DrawPixel>()
+            .add_message::<DrawRect>()
+            .add_message::<DrawSpan>()
+            .add_message::<DrawIndex>()
+            .add_message::<DrawIndexRect>();
+
+        // Resources
+        app.insert_resource(self.config.clone()).init_resource::<CanvasPalette>();
+
+        // Systems
+        app.add_systems(Startup, spawn_canvas_images);
+        match self.config.format() {
+            CanvasFormat::Rgba => {
+                app.add_systems(Update, collect_ops);
+            }
+            CanvasFormat::Indexed => {
+                embedded_asset!(app, "indexed_canvas.wgsl");
+                app.add_plugins(Material2dPlugin::<IndexedCanvasMaterial>::default())
+                    .add_systems(Update, (sync_canvas_palette, collect_indexed_ops));
+            }
+        }
+
+        // Render-world systems
+        app.sub_app_mut(RenderApp)
+            .add_systems(Render, apply_canvas_uploads.in_set(RenderSystems::Queue));
+    }
+}
@@ -0,0 +1,23 @@
+use bevy::{
+    asset::{AssetPath, embedded_path},
+    prelude::*,
+    render::render_resource::AsBindGroup,
+    shader::ShaderRef,
+    sprite_render::Material2d,
+};
+
+/// Colours one chunk of an indexed canvas by looking up each pixel's index in the palette texture.
+#[derive(Asset, TypePath, AsBindGroup, Debug, Clone)]
+pub struct IndexedCanvasMaterial {
+    #[texture(0, sample_type = "u_int")]
+    pub indices: Handle<Image>,
+
+    #[texture(1)]
+    pub palette: Handle<Image>,
+}
+
+impl Material2d for IndexedCanvasMaterial {
+    fn fragment_shader() -> ShaderRef {
+        ShaderRef::Path(AssetPath::from_path_buf(embedded_path!("indexed_canvas.wgsl")).with_source("embedded"))
+    }
+}
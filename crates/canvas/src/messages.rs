@@ -16,6 +16,23 @@ pub struct DrawRect {
     pub rgba_u32: Vec<u32>, // Length == w*h (one u32 per pixel)
 }
 
+/// Draw a single palette index to the canvas.
+#[derive(Message)]
+pub struct DrawIndex {
+    pub pos: UVec2, // Canvas coords, bottom-left origin
+    pub index: u8,  // Entry in `CanvasPalette`
+}
+
+/// Draw a rectangular region of palette indices to the canvas.
+/// The region will wrap toroidally if it exceeds canvas bounds.
+/// On an RGBA canvas the indices are converted through `CanvasPalette` as they are drawn.
+#[derive(Message)]
+pub struct DrawIndexRect {
+    pub start: UVec2,     // Canvas coords, bottom-left origin
+    pub size: UVec2,      // Rect region dimensions (width, height)
+    pub indices: Vec<u8>, // Length == w*h (one index per pixel)
+}
+
 /// Draw a contiguous horizontal span to the canvas.
 /// If the span exceeds the canvas width, it will continue to the next row upwards.
 /// If the span exceeds the canvas height, it will wrap to the bottom.
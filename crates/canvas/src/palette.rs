@@ -0,0 +1,74 @@
+use bevy::prelude::*;
+
+/// Number of entries in a palette, one per possible index.
+pub const PALETTE_SIZE: usize = 256;
+
+/// Colours that palette indices stand for, as packed RGBA8 values.
+/// An indexed canvas looks colours up on the GPU, and an RGBA canvas converts index messages on the CPU.
+#[derive(Resource, Clone, PartialEq, Eq)]
+pub struct CanvasPalette {
+    colours: [u32; PALETTE_SIZE],
+}
+
+impl CanvasPalette {
+    /// Palette starting with `colours`, with any remaining entries transparent.
+    pub fn new(colours: &[u32]) -> Self {
+        debug_assert!(colours.len() <= PALETTE_SIZE);
+
+        let mut palette = Self {
+            colours: [0; PALETTE_SIZE],
+        };
+        palette.colours[..colours.len()].copy_from_slice(colours);
+        palette
+    }
+
+    #[inline(always)]
+    pub fn colour(&self, index: u8) -> u32 {
+        self.colours[index as usize]
+    }
+
+    #[inline]
+    pub fn colours(&self) -> &[u32; PALETTE_SIZE] {
+        &self.colours
+    }
+
+    #[inline]
+    pub fn set(&mut self, index: u8, rgba_u32: u32) {
+        self.colours[index as usize] = rgba_u32;
+    }
+
+    /// Convert indices to packed RGBA8 values, writing one per index into `dst`.
+    #[inline]
+    pub fn resolve_into(&self, indices: &[u8], dst: &mut [u32]) {
+        debug_assert_eq!(indices.len(), dst.len());
+        for (dst, &index) in dst.iter_mut().zip(indices) {
+            *dst = self.colour(index);
+        }
+    }
+
+    /// Convert indices to packed RGBA8 values.
+    pub fn resolve(&self, indices: &[u8]) -> Vec<u32> {
+        let mut rgba_u32 = vec![0; indices.len()];
+        self.resolve_into(indices, &mut rgba_u32);
+        rgba_u32
+    }
+
+    /// RGBA8 bytes of the `PALETTE_SIZE` x 1 texture the indexed canvas material reads colours from.
+    pub fn texture_bytes(&self) -> Vec<u8> {
+        bytemuck::cast_slice(&self.colours).to_vec()
+    }
+}
+
+impl Default for CanvasPalette {
+    /// Opaque greyscale ramp, from black at index 0 to white at index 255.
+    fn default() -> Self {
+        let mut palette = Self {
+            colours: [0; PALETTE_SIZE],
+        };
+        for index in 0..PALETTE_SIZE {
+            let level = index as u8;
+            palette.colours[index] = u32::from_ne_bytes([level, level, level, 255]);
+        }
+        palette
+    }
+}
@@ -24,15 +24,21 @@ impl CanvasImageHandles {
     }
 }
 
-/// CPU backing store: row-major u32 pixels per chunk.
+/// Palette texture shared by every chunk of an indexed canvas.
 #[derive(Resource)]
-pub struct CanvasCpuChunks {
+pub struct CanvasPaletteImage {
+    pub handle: Handle<Image>,
+}
+
+/// CPU backing store: row-major pixels per chunk, packed RGBA8 `u32`s or `u8` palette indices.
+#[derive(Resource)]
+pub struct CanvasCpuChunks<T: Send + Sync + 'static = u32> {
     num_chunks: U8Vec2,
-    chunk_data: Vec<Vec<u32>>,
+    chunk_data: Vec<Vec<T>>,
 }
 
-impl CanvasCpuChunks {
-    pub fn new(num_chunks: U8Vec2, chunk_size: UVec2, default_px: u32) -> Self {
+impl<T: Copy + Send + Sync + 'static> CanvasCpuChunks<T> {
+    pub fn new(num_chunks: U8Vec2, chunk_size: UVec2, default_px: T) -> Self {
         debug_assert!(num_chunks.x > 0);
         debug_assert!(num_chunks.y > 0);
         debug_assert!(chunk_size.x > 0);
@@ -55,13 +61,13 @@ impl CanvasCpuChunks {
         chunk_key.y as usize * self.num_chunks.x as usize + chunk_key.x as usize
     }
 
-    pub fn chunk(&self, index: usize) -> &[u32] {
+    pub fn chunk(&self, index: usize) -> &[T] {
         debug_assert!(index < self.chunk_data.len());
         &self.chunk_data[index]
     }
 
     #[inline]
-    pub fn chunk_mut(&mut self, chunk_key: &U8Vec2) -> &mut [u32] {
+    pub fn chunk_mut(&mut self, chunk_key: &U8Vec2) -> &mut [T] {
         debug_assert!(chunk_key.x < self.num_chunks.x);
         debug_assert!(chunk_key.y < self.num_chunks.y);
 
@@ -1,5 +1,6 @@
 use bevy::{
     asset::RenderAssetUsages,
+    ecs::system::SystemParam,
     image::ImageSampler,
     math::U8Vec2,
     prelude::*,
@@ -13,26 +14,73 @@ use bevy::{
         texture::GpuImage,
     },
 };
+use bytemuck::Pod;
 
 use super::{
     components::CanvasImage,
-    config::CanvasConfig,
-    messages::{DrawPixel, DrawRect, DrawSpan},
-    resources::{CanvasCpuChunks, CanvasDirtyRects, CanvasImageHandles, CanvasUploadOps},
+    config::{CanvasConfig, CanvasFormat},
+    material::IndexedCanvasMaterial,
+    messages::{DrawIndex, DrawIndexRect, DrawPixel, DrawRect, DrawSpan},
+    palette::{CanvasPalette, PALETTE_SIZE},
+    resources::{CanvasCpuChunks, CanvasDirtyRects, CanvasImageHandles, CanvasPaletteImage, CanvasUploadOps},
     types::{CanvasUploadOp, SubRect},
 };
 
-pub fn spawn_canvas_images(mut commands: Commands, config: Res<CanvasConfig>, mut images: ResMut<Assets<Image>>) {
+/// Assets needed to spawn an indexed canvas's chunks.
+#[derive(SystemParam)]
+pub struct IndexedCanvasAssets<'w> {
+    palette: Res<'w, CanvasPalette>,
+    meshes: ResMut<'w, Assets<Mesh>>,
+    materials: ResMut<'w, Assets<IndexedCanvasMaterial>>,
+}
+
+/// Spawn one image per chunk, drawn as a sprite or, for an indexed canvas, through `IndexedCanvasMaterial`.
+pub fn spawn_canvas_images(
+    mut commands: Commands,
+    config: Res<CanvasConfig>,
+    mut images: ResMut<Assets<Image>>,
+    indexed: Option<IndexedCanvasAssets>,
+) -> Result {
     let num_chunks = config.num_chunks();
     let chunk_size = config.chunk_size();
     let pixels_per_chunk = config.pixels_per_chunk();
 
-    // Create default RGBA8 data for one chunk
-    let [r, g, b, a] = *config.clear_colour();
-    let mut data = vec![0u8; pixels_per_chunk * 4];
-    for px in data.chunks_exact_mut(4) {
-        px.copy_from_slice(&[r, g, b, a]);
-    }
+    // Create default data for one chunk: the clear colour, or palette index 0
+    let (data, texture_format) = match config.format() {
+        CanvasFormat::Rgba => {
+            let [r, g, b, a] = *config.clear_colour();
+            let mut data = vec![0u8; pixels_per_chunk * 4];
+            for px in data.chunks_exact_mut(4) {
+                px.copy_from_slice(&[r, g, b, a]);
+            }
+            (data, TextureFormat::Rgba8UnormSrgb)
+        }
+        CanvasFormat::Indexed => (vec![0u8; pixels_per_chunk], TextureFormat::R8Uint),
+    };
+
+    // Indexed chunks share a palette texture and a quad mesh
+    let mut indexed = match (config.format(), indexed) {
+        (CanvasFormat::Rgba, _) => None,
+        (CanvasFormat::Indexed, Some(indexed)) => Some(indexed),
+        (CanvasFormat::Indexed, None) => {
+            return Err("indexed canvas is missing its palette, meshes or IndexedCanvasMaterial assets".into());
+        }
+    };
+    let mut shared = indexed.as_mut().map(|indexed| {
+        let palette = images.add(Image::new(
+            Extent3d {
+                width: PALETTE_SIZE as u32,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            TextureDimension::D2,
+            indexed.palette.texture_bytes(),
+            TextureFormat::Rgba8UnormSrgb,
+            RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
+        ));
+        let mesh = indexed.meshes.add(Rectangle::from_size(chunk_size.as_vec2()));
+        (palette, mesh, &mut indexed.materials)
+    });
 
     let mut image_handles = Vec::with_capacity(config.total_chunks());
     for y in 0..num_chunks.y {
@@ -46,7 +94,7 @@ pub fn spawn_canvas_images(mut commands: Commands, config: Res<CanvasConfig>, mu
                 },
                 TextureDimension::D2,
                 data.clone(),
-                TextureFormat::Rgba8UnormSrgb,
+                texture_format,
                 RenderAssetUsages::MAIN_WORLD | RenderAssetUsages::RENDER_WORLD,
             );
             image.texture_descriptor.usage |= TextureUsages::COPY_DST;
@@ -56,17 +104,25 @@ pub fn spawn_canvas_images(mut commands: Commands, config: Res<CanvasConfig>, mu
             let handle = images.add(image);
             image_handles.push(handle.clone());
 
-            // Spawn a sprite entity to render this chunk
-            commands.spawn((
-                CanvasImage,
-                Sprite::from_image(handle),
-                Transform::from_translation(Vec3::new(
-                    (x as f32 - ((num_chunks.x - 1) as f32 / 2.0)) * chunk_size.x as f32,
-                    (y as f32 - ((num_chunks.y - 1) as f32 / 2.0)) * chunk_size.y as f32,
-                    config.canvas_z_index(),
-                ))
-                .with_scale(Vec3::new(1.0, -1.0, 1.0)),
-            ));
+            // Spawn an entity to render this chunk, as a sprite or through the palette material
+            let transform = Transform::from_translation(Vec3::new(
+                (x as f32 - ((num_chunks.x - 1) as f32 / 2.0)) * chunk_size.x as f32,
+                (y as f32 - ((num_chunks.y - 1) as f32 / 2.0)) * chunk_size.y as f32,
+                config.canvas_z_index(),
+            ))
+            .with_scale(Vec3::new(1.0, -1.0, 1.0));
+            match &mut shared {
+                Some((palette, mesh, materials)) => {
+                    let material = materials.add(IndexedCanvasMaterial {
+                        indices: handle,
+                        palette: palette.clone(),
+                    });
+                    commands.spawn((CanvasImage, Mesh2d(mesh.clone()), MeshMaterial2d(material), transform));
+                }
+                None => {
+                    commands.spawn((CanvasImage, Sprite::from_image(handle), transform));
+                }
+            }
         }
     }
 
@@ -74,20 +130,33 @@ pub fn spawn_canvas_images(mut commands: Commands, config: Res<CanvasConfig>, mu
     commands.insert_resource(CanvasImageHandles::new(num_chunks, image_handles));
 
     // Create CPU chunk storage
-    let [r, g, b, a] = *config.clear_colour();
-    let default_px = u32::from_ne_bytes([r, g, b, a]);
-    commands.insert_resource(CanvasCpuChunks::new(num_chunks, chunk_size, default_px));
+    match shared {
+        Some((palette, _, _)) => {
+            commands.insert_resource(CanvasCpuChunks::<u8>::new(num_chunks, chunk_size, 0));
+            commands.insert_resource(CanvasPaletteImage { handle: palette });
+        }
+        None => {
+            let [r, g, b, a] = *config.clear_colour();
+            let default_px = u32::from_ne_bytes([r, g, b, a]);
+            commands.insert_resource(CanvasCpuChunks::new(num_chunks, chunk_size, default_px));
+        }
+    }
 
     // Create dirty rects and upload ops resources
     commands.insert_resource(CanvasDirtyRects::new(num_chunks, chunk_size));
     commands.insert_resource(CanvasUploadOps::default());
+    Ok(())
 }
 
+#[allow(clippy::too_many_arguments)] // Bevy system parameters
 pub fn collect_ops(
     config: Res<CanvasConfig>,
+    palette: Res<CanvasPalette>,
     mut draw_pixel_msg: MessageReader<DrawPixel>,
     mut draw_rect_msg: MessageReader<DrawRect>,
     mut draw_span_msg: MessageReader<DrawSpan>,
+    mut draw_index_msg: MessageReader<DrawIndex>,
+    mut draw_index_rect_msg: MessageReader<DrawIndexRect>,
     canvas_image_handles: Res<CanvasImageHandles>,
     mut canvas_cpu_chunks: ResMut<CanvasCpuChunks>,
     mut canvas_dirty_rects: ResMut<CanvasDirtyRects>,
@@ -135,6 +204,33 @@ pub fn collect_ops(
         );
     }
 
+    // Palette indices, converted to colours
+    for DrawIndex { pos, index } in draw_index_msg.read() {
+        blit_pixel(
+            &mut canvas_cpu_chunks,
+            &mut canvas_dirty_rects,
+            canvas_size,
+            chunk_size,
+            *pos,
+            palette.colour(*index),
+        );
+    }
+    for DrawIndexRect { start, size, indices } in draw_index_rect_msg.read() {
+        if !index_rect_is_valid(*size, indices) {
+            continue;
+        }
+
+        blit_rect_torus_into_chunks(
+            &mut canvas_cpu_chunks,
+            &mut canvas_dirty_rects,
+            *start,
+            *size,
+            &palette.resolve(indices),
+            canvas_size,
+            chunk_size,
+        );
+    }
+
     // Spans
     for DrawSpan { start, rgba_u32 } in draw_span_msg.read() {
         if rgba_u32.is_empty() {
@@ -160,6 +256,71 @@ pub fn collect_ops(
     );
 }
 
+/// Write palette indices into an indexed canvas, leaving the colours to its material.
+pub fn collect_indexed_ops(
+    config: Res<CanvasConfig>,
+    mut draw_index_msg: MessageReader<DrawIndex>,
+    mut draw_index_rect_msg: MessageReader<DrawIndexRect>,
+    canvas_image_handles: Res<CanvasImageHandles>,
+    mut canvas_cpu_chunks: ResMut<CanvasCpuChunks<u8>>,
+    mut canvas_dirty_rects: ResMut<CanvasDirtyRects>,
+    mut canvas_upload_ops: ResMut<CanvasUploadOps>,
+) {
+    let canvas_size = config.canvas_size();
+    let chunk_size = config.chunk_size();
+
+    // Indices
+    for DrawIndex { pos, index } in draw_index_msg.read() {
+        blit_pixel(
+            &mut canvas_cpu_chunks,
+            &mut canvas_dirty_rects,
+            canvas_size,
+            chunk_size,
+            *pos,
+            *index,
+        );
+    }
+
+    // Index rects
+    for DrawIndexRect { start, size, indices } in draw_index_rect_msg.read() {
+        if !index_rect_is_valid(*size, indices) {
+            continue;
+        }
+
+        blit_rect_torus_into_chunks(
+            &mut canvas_cpu_chunks,
+            &mut canvas_dirty_rects,
+            *start,
+            *size,
+            indices,
+            canvas_size,
+            chunk_size,
+        );
+    }
+
+    build_upload_ops(
+        &canvas_image_handles,
+        &canvas_cpu_chunks,
+        &mut canvas_dirty_rects,
+        &mut canvas_upload_ops,
+        chunk_size,
+    );
+}
+
+/// Re-upload the palette texture when `CanvasPalette` changes.
+pub fn sync_canvas_palette(
+    palette: Res<CanvasPalette>,
+    palette_image: Res<CanvasPaletteImage>,
+    mut images: ResMut<Assets<Image>>,
+) {
+    if !palette.is_changed() {
+        return;
+    }
+    if let Some(image) = images.get_mut(&palette_image.handle) {
+        image.data = Some(palette.texture_bytes());
+    }
+}
+
 pub fn apply_canvas_uploads(
     mut uploads: ResMut<CanvasUploadOps>,
     gpu_images: Res<RenderAssets<GpuImage>>,
@@ -209,13 +370,31 @@ pub fn apply_canvas_uploads(
 // -- Helpers --
 
 #[inline]
-fn blit_pixel(
-    cpu: &mut CanvasCpuChunks,
+fn index_rect_is_valid(size: UVec2, indices: &[u8]) -> bool {
+    if size.x == 0 || size.y == 0 {
+        return false;
+    }
+
+    let expected = (size.x * size.y) as usize;
+    if indices.len() != expected {
+        warn!(
+            "DrawIndexRect indices length mismatch (expected {}, got {})",
+            expected,
+            indices.len()
+        );
+        return false;
+    }
+    true
+}
+
+#[inline]
+fn blit_pixel<T: Copy + Send + Sync + 'static>(
+    cpu: &mut CanvasCpuChunks<T>,
     dirty: &mut CanvasDirtyRects,
     canvas_size: UVec2,
     chunk_size: UVec2,
     pos: UVec2,
-    rgba_u32: u32,
+    px: T,
 ) {
     debug_assert!(canvas_size.x > 0 && canvas_size.y > 0);
     debug_assert!(chunk_size.x > 0 && chunk_size.y > 0);
@@ -238,25 +417,25 @@ fn blit_pixel(
     let stride = chunk_size.x as usize;
     let idx = local.y as usize * stride + local.x as usize;
     debug_assert!(idx < dst.len());
-    dst[idx] = rgba_u32;
+    dst[idx] = px;
 
     // Dirty 1x1
     dirty.mark_rect(&chunk_key, local, UVec2::ONE);
 }
 
 #[inline]
-fn blit_rect_torus_into_chunks(
-    canvas_cpu_chunks: &mut CanvasCpuChunks,
+fn blit_rect_torus_into_chunks<T: Copy + Send + Sync + 'static>(
+    canvas_cpu_chunks: &mut CanvasCpuChunks<T>,
     canvas_dirty_rects: &mut CanvasDirtyRects,
     start: UVec2,
     size: UVec2,
-    src_u32: &[u32],
+    src: &[T],
     canvas_size: UVec2,
     chunk_size: UVec2,
 ) {
     let (sub_rects, n) = split_torus_rect(start, size, canvas_size);
     for sub_rect in sub_rects[..n].iter().copied() {
-        blit_subrect_into_chunks(canvas_cpu_chunks, canvas_dirty_rects, size, src_u32, sub_rect, chunk_size);
+        blit_subrect_into_chunks(canvas_cpu_chunks, canvas_dirty_rects, size, src, sub_rect, chunk_size);
     }
 }
 
@@ -324,11 +503,11 @@ fn split_torus_rect(start: UVec2, size: UVec2, canvas_size: UVec2) -> ([SubRect;
 }
 
 #[inline]
-fn blit_subrect_into_chunks(
-    canvas_cpu_chunks: &mut CanvasCpuChunks,
+fn blit_subrect_into_chunks<T: Copy + Send + Sync + 'static>(
+    canvas_cpu_chunks: &mut CanvasCpuChunks<T>,
     canvas_dirty_rects: &mut CanvasDirtyRects,
     orig_size: UVec2,
-    src_u32: &[u32],
+    src: &[T],
     sub_rect: SubRect,
     chunk_size: UVec2, // (chunk_w, chunk_h)
 ) {
@@ -371,7 +550,7 @@ fn blit_subrect_into_chunks(
 
             let src_min = sub_rect.src_start + (intersection_min - sub_rect.dst_start);
 
-            let dst_u32 = canvas_cpu_chunks.chunk_mut(&chunk_key);
+            let dst = canvas_cpu_chunks.chunk_mut(&chunk_key);
 
             // Row stride is chunk_w
             let dst_stride = chunk_w as usize;
@@ -379,10 +558,10 @@ fn blit_subrect_into_chunks(
             for row in 0..rows {
                 let src_index = (src_min.y as usize + row) * src_stride + (src_min.x as usize);
                 let dst_index = (local_min.y as usize + row) * dst_stride + (local_min.x as usize);
-                debug_assert!(src_index + copy <= src_u32.len());
-                debug_assert!(dst_index + copy <= dst_u32.len());
+                debug_assert!(src_index + copy <= src.len());
+                debug_assert!(dst_index + copy <= dst.len());
 
-                dst_u32[dst_index..dst_index + copy].copy_from_slice(&src_u32[src_index..src_index + copy]);
+                dst[dst_index..dst_index + copy].copy_from_slice(&src[src_index..src_index + copy]);
             }
 
             canvas_dirty_rects.mark_rect(&chunk_key, local_min, intersection_size);
@@ -392,9 +571,9 @@ fn blit_subrect_into_chunks(
 
 /// Convert dirty rects to upload ops (CPU snapshot of touched rows)
 #[inline]
-fn build_upload_ops(
+fn build_upload_ops<T: Pod + Send + Sync>(
     canvas_image_handles: &CanvasImageHandles,
-    canvas_cpu_chunks: &CanvasCpuChunks,
+    canvas_cpu_chunks: &CanvasCpuChunks<T>,
     canvas_dirty_rects: &mut CanvasDirtyRects,
     canvas_upload_ops: &mut CanvasUploadOps,
     chunk_size: UVec2,
@@ -404,7 +583,8 @@ fn build_upload_ops(
     let chunk_w = chunk_size.x;
     let chunk_h = chunk_size.y;
 
-    const ROW_ALIGN_PX: u32 = 64;
+    // Rows must start on 256 byte boundaries
+    let row_align_px = 256 / size_of::<T>() as u32;
 
     for chunk_index in 0..canvas_dirty_rects.len() {
         let Some((min, max)) = canvas_dirty_rects.take(chunk_index) else {
@@ -421,9 +601,9 @@ fn build_upload_ops(
         );
         let max_ex = UVec2::new(max_ex.x.min(chunk_w), max_ex.y.min(chunk_h));
 
-        // Pad X to 64px for bytes_per_row alignment
-        let padding_min_x = (min_ex.x / ROW_ALIGN_PX) * ROW_ALIGN_PX;
-        let padding_max_x = (((max_ex.x + ROW_ALIGN_PX - 1) / ROW_ALIGN_PX) * ROW_ALIGN_PX).min(chunk_w);
+        // Pad X for bytes_per_row alignment
+        let padding_min_x = (min_ex.x / row_align_px) * row_align_px;
+        let padding_max_x = (max_ex.x.div_ceil(row_align_px) * row_align_px).min(chunk_w);
 
         let padded_width = padding_max_x.saturating_sub(padding_min_x);
         if padded_width == 0 {
@@ -435,7 +615,7 @@ fn build_upload_ops(
             continue;
         }
 
-        let bytes_per_row = padded_width * 4;
+        let bytes_per_row = padded_width * size_of::<T>() as u32;
         debug_assert_eq!(bytes_per_row % 256, 0);
 
         let handle = canvas_image_handles.handle(chunk_index).clone();
@@ -465,18 +645,18 @@ fn build_upload_ops(
 }
 
 #[inline]
-fn blit_span_row_major(
-    cpu: &mut CanvasCpuChunks,
+fn blit_span_row_major<T: Copy + Send + Sync + 'static>(
+    cpu: &mut CanvasCpuChunks<T>,
     dirty: &mut CanvasDirtyRects,
     canvas_size: UVec2,
     chunk_size: UVec2,
     start: UVec2,
-    src_u32: &[u32],
+    src: &[T],
 ) {
     debug_assert!(canvas_size.x > 0 && canvas_size.y > 0);
     debug_assert!(chunk_size.x > 0 && chunk_size.y > 0);
 
-    if src_u32.is_empty() {
+    if src.is_empty() {
         return;
     }
 
@@ -484,7 +664,7 @@ fn blit_span_row_major(
     let mut cursor = UVec2::new(start.x % canvas_size.x, start.y % canvas_size.y);
 
     let mut src_index = 0usize;
-    let mut remaining = src_u32.len();
+    let mut remaining = src.len();
 
     while remaining > 0 {
         // Chunk coords and chunk-local coords
@@ -509,9 +689,9 @@ fn blit_span_row_major(
         let dst_index = (local_xy.y as usize) * dst_stride + (local_xy.x as usize);
 
         debug_assert!(dst_index + run <= dst.len());
-        debug_assert!(src_index + run <= src_u32.len());
+        debug_assert!(src_index + run <= src.len());
 
-        dst[dst_index..dst_index + run].copy_from_slice(&src_u32[src_index..src_index + run]);
+        dst[dst_index..dst_index + run].copy_from_slice(&src[src_index..src_index + run]);
 
         // Mark dirty: a 1-row-high span in chunk-local coords
         dirty.mark_rect(&chunk_key, UVec2::new(local_xy.x, local_xy.y), UVec2::new(run as u32, 1));
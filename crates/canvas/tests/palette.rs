@@ -0,0 +1,60 @@
+use arc_canvas::palette::{CanvasPalette, PALETTE_SIZE};
+
+fn rgba(r: u8, g: u8, b: u8, a: u8) -> u32 {
+    u32::from_ne_bytes([r, g, b, a])
+}
+
+#[test]
+fn resolves_indices_through_the_palette() {
+    let palette = CanvasPalette::new(&[rgba(255, 255, 255, 255), rgba(0, 0, 0, 255), rgba(255, 0, 0, 255)]);
+    assert_eq!(
+        palette.resolve(&[0, 1, 2, 1, 0]),
+        [
+            rgba(255, 255, 255, 255),
+            rgba(0, 0, 0, 255),
+            rgba(255, 0, 0, 255),
+            rgba(0, 0, 0, 255),
+            rgba(255, 255, 255, 255),
+        ]
+    );
+
+    // Entries past the given colours are transparent
+    assert_eq!(palette.resolve(&[3, 255]), [0, 0]);
+}
+
+#[test]
+fn resolves_into_an_existing_buffer() {
+    let palette = CanvasPalette::new(&[rgba(1, 2, 3, 4), rgba(5, 6, 7, 8)]);
+    let mut dst = [0; 4];
+    palette.resolve_into(&[1, 0, 0, 1], &mut dst);
+    assert_eq!(dst, [rgba(5, 6, 7, 8), rgba(1, 2, 3, 4), rgba(1, 2, 3, 4), rgba(5, 6, 7, 8)]);
+}
+
+#[test]
+fn changing_a_colour_changes_its_pixels() {
+    let mut palette = CanvasPalette::new(&[rgba(0, 0, 0, 255); 2]);
+    palette.set(1, rgba(0, 255, 0, 255));
+    assert_eq!(palette.colour(1), rgba(0, 255, 0, 255));
+    assert_eq!(palette.resolve(&[0, 1]), [rgba(0, 0, 0, 255), rgba(0, 255, 0, 255)]);
+}
+
+#[test]
+fn default_palette_is_a_greyscale_ramp() {
+    let palette = CanvasPalette::default();
+    assert_eq!(palette.colour(0), rgba(0, 0, 0, 255));
+    assert_eq!(palette.colour(128), rgba(128, 128, 128, 255));
+    assert_eq!(palette.colour(255), rgba(255, 255, 255, 255));
+}
+
+#[test]
+fn texture_bytes_are_rgba_in_index_order() {
+    let palette = CanvasPalette::new(&[rgba(10, 20, 30, 40), rgba(50, 60, 70, 80)]);
+    let bytes = palette.texture_bytes();
+    assert_eq!(bytes.len(), PALETTE_SIZE * 4);
+    assert_eq!(bytes[..8], [10, 20, 30, 40, 50, 60, 70, 80]);
+    assert!(bytes[8..].iter().all(|&byte| byte == 0));
+
+    // Same bytes an RGBA canvas would upload for the resolved pixels
+    let resolved = palette.resolve(&[0, 1]);
+    assert_eq!(bytemuck::cast_slice::<u32, u8>(&resolved), &bytes[..8]);
+}
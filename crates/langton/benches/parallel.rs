@@ -0,0 +1,81 @@
+//! Step the same turmites on increasing numbers of threads, checking each run ends on the same board.
+//!
+//! Run with `cargo bench -p arc_langton`.
+
+use std::{
+    hint::black_box,
+    num::NonZero,
+    thread,
+    time::{Duration, Instant},
+};
+
+use arc_langton::{
+    components::{Heading, Turmite},
+    debugger::Debugger,
+    resources::{Memory, SimulationStats},
+    settings::{BOARD_SIZE, STEPS_PER_TICK},
+    simulation::{Board, TickTurmite},
+};
+use bevy::prelude::*;
+
+/// Timed runs of each thread count, keeping the fastest.
+const RUNS: usize = 3;
+const TICKS: usize = 10;
+
+fn main() {
+    let available = thread::available_parallelism().map_or(1, NonZero::get);
+    for grid in [4, 16] {
+        let steps = TICKS * STEPS_PER_TICK * (grid * grid) as usize;
+        println!("{} turmites:", grid * grid);
+
+        let (baseline, expected) = time(grid, 1);
+        report(1, baseline, baseline, steps);
+        let mut threads = 2;
+        while threads <= available {
+            let (elapsed, data) = time(grid, threads);
+            assert!(data == expected, "{threads} threads left a different board");
+            report(threads, elapsed, baseline, steps);
+            threads *= 2;
+        }
+    }
+}
+
+/// Turmites spread evenly over the board, in a `grid` by `grid` square.
+fn turmites(grid: u32) -> Vec<TickTurmite> {
+    let spacing = BOARD_SIZE / grid;
+    (0..grid * grid)
+        .map(|index| TickTurmite {
+            entity: Entity::from_raw_u32(index).unwrap(),
+            turmite: Turmite::new(
+                UVec2::new(index % grid, index / grid) * spacing + spacing / 2,
+                0,
+                Heading::North,
+            ),
+            program: None,
+        })
+        .collect()
+}
+
+fn time(grid: u32, threads: usize) -> (Duration, Vec<u8>) {
+    (0..RUNS)
+        .map(|_| {
+            let memory = Memory::default();
+            let mut stats = SimulationStats::from_memory(&memory);
+            let mut board = Board::new(memory).with_threads(threads);
+            let mut turmites = turmites(grid);
+            let mut debugger = Debugger::default();
+            let start = Instant::now();
+            for _ in 0..TICKS {
+                black_box(board.tick(&mut turmites, &mut debugger, &mut stats));
+            }
+            (start.elapsed(), board.memory().data().to_vec())
+        })
+        .min_by_key(|(elapsed, _)| *elapsed)
+        .unwrap()
+}
+
+fn report(threads: usize, elapsed: Duration, baseline: Duration, steps: usize) {
+    let per_step = elapsed.as_secs_f64() * 1e9 / steps as f64;
+    let speedup = baseline.as_secs_f64() / elapsed.as_secs_f64();
+    println!("  {threads:>3} threads  {per_step:>9.1} ns/step  {speedup:>5.2}x");
+}
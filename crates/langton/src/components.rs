@@ -0,0 +1,184 @@
+use std::{f32::consts::FRAC_PI_2, fmt, sync::Arc};
+
+use arc_vm::prelude::*;
+use bevy::prelude::*;
+
+use crate::settings::{BOARD_SIZE, MARKER_Z_INDEX};
+
+/// Arrowhead pointing North, filling one cell.
+pub const MARKER: [Vec2; 3] = [Vec2::new(0.0, 0.5), Vec2::new(-0.4, -0.4), Vec2::new(0.4, -0.4)];
+
+#[derive(Component, Clone)]
+pub struct Turmite {
+    pub(crate) pos: UVec2,
+    pub(crate) state: u8,
+    pub(crate) heading: Heading,
+}
+
+impl Turmite {
+    pub fn new(pos: UVec2, state: u8, heading: Heading) -> Self {
+        Self { pos, state, heading }
+    }
+
+    // -- Getters --
+
+    #[inline]
+    pub fn pos(&self) -> UVec2 {
+        self.pos
+    }
+
+    #[inline]
+    pub fn state(&self) -> u8 {
+        self.state
+    }
+
+    #[inline]
+    pub fn heading(&self) -> Heading {
+        self.heading
+    }
+}
+
+/// The single mesh holding every swarm turmite's marker, rebuilt once per tick.
+#[derive(Component)]
+pub struct SwarmMarkers;
+
+/// Direction a turmite is facing, in clockwise order.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Heading {
+    #[default]
+    North,
+    East,
+    South,
+    West,
+}
+
+impl Heading {
+    pub const ALL: [Heading; 4] = [Heading::North, Heading::East, Heading::South, Heading::West];
+
+    /// One cell step in this direction, with North as +y.
+    #[inline]
+    pub fn delta(self) -> IVec2 {
+        match self {
+            Heading::North => IVec2::Y,
+            Heading::East => IVec2::X,
+            Heading::South => IVec2::NEG_Y,
+            Heading::West => IVec2::NEG_X,
+        }
+    }
+
+    /// Heading of a single orthogonal step, if `delta` is one.
+    #[inline]
+    pub fn from_delta(delta: IVec2) -> Option<Self> {
+        Self::ALL.into_iter().find(|heading| heading.delta() == delta)
+    }
+
+    /// Rotate by a number of quarter turns, clockwise for positive values.
+    #[inline]
+    pub fn turned(self, quarter_turns: i64) -> Self {
+        Self::ALL[(self as i64 + quarter_turns).rem_euclid(4) as usize]
+    }
+
+    /// Marker rotation, assuming the marker points North when unrotated.
+    #[inline]
+    pub fn rotation(self) -> Quat {
+        Quat::from_rotation_z(-(self as u8 as f32) * FRAC_PI_2)
+    }
+}
+
+/// World position of the centre of a cell, at the height markers are drawn at.
+pub fn coord_to_world_pos(coord: UVec2) -> Vec3 {
+    (Vec2::new(coord.x as f32, coord.y as f32) + Vec2::splat(0.5)
+        - Vec2::new(BOARD_SIZE.x as f32 * 0.5, BOARD_SIZE.y as f32 * 0.5))
+    .extend(MARKER_Z_INDEX)
+}
+
+/// Bytecode controller that drives a turmite in place of the transition table.
+#[derive(Component, Clone)]
+pub struct TurmiteProgram {
+    pub(crate) program: Arc<Executable>,
+    pub(crate) vm: Vm,
+    pub(crate) status: ProgramStatus,
+    /// Limit the program ran into, until the limit policy has been applied.
+    pub(crate) exceeded: Option<Limit>,
+    /// Instructions already added to the simulation stats.
+    pub(crate) reported: u64,
+    /// Value of the cell under the turmite when its current step began, kept while the debugger pauses partway
+    /// through the step.
+    pub(crate) step_input: Option<u8>,
+}
+
+impl TurmiteProgram {
+    pub fn new(program: Arc<Executable>, limits: Limits) -> Self {
+        Self {
+            program,
+            vm: Vm::with_limits(limits),
+            status: ProgramStatus::Running,
+            exceeded: None,
+            reported: 0,
+            step_input: None,
+        }
+    }
+
+    /// Swap in another program, starting it afresh. The turmite, the board and the VM's registers are left as they are.
+    pub fn replace(&mut self, program: Arc<Executable>) {
+        self.program = program;
+        self.vm.reset();
+        self.status = ProgramStatus::Running;
+        self.exceeded = None;
+        self.step_input = None;
+    }
+
+    /// Start the program over with a fresh VM, clearing its registers and refilling its fuel.
+    pub fn restart(&mut self) {
+        *self = Self::new(self.program.clone(), *self.vm.limits());
+    }
+
+    // -- Getters --
+
+    #[inline]
+    pub fn program(&self) -> &Arc<Executable> {
+        &self.program
+    }
+
+    #[inline]
+    pub fn vm(&self) -> &Vm {
+        &self.vm
+    }
+
+    #[inline]
+    pub fn status(&self) -> &ProgramStatus {
+        &self.status
+    }
+
+    // -- Helpers --
+
+    /// Instructions run since the last call, for the simulation stats.
+    pub(crate) fn take_executed(&mut self) -> u64 {
+        let executed = self.vm.executed() - self.reported;
+        self.reported = self.vm.executed();
+        executed
+    }
+}
+
+/// Whether a program-driven turmite is still stepping.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProgramStatus {
+    Running,
+    /// The program reached `HALT`.
+    Halted,
+    /// The program ran into one of its VM limits.
+    LimitExceeded(Limit),
+    /// The program failed with a runtime error.
+    Faulted(VmError),
+}
+
+impl fmt::Display for ProgramStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProgramStatus::Running => write!(f, "running"),
+            ProgramStatus::Halted => write!(f, "halted"),
+            ProgramStatus::LimitExceeded(limit) => write!(f, "stopped by its {limit} limit"),
+            ProgramStatus::Faulted(err) => write!(f, "faulted: {err}"),
+        }
+    }
+}
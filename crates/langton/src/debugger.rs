@@ -0,0 +1,234 @@
+use std::{collections::BTreeSet, fmt};
+
+use bevy::prelude::*;
+
+use crate::{
+    components::{ProgramStatus, Turmite, TurmiteProgram},
+    host::{Cells, Crowd},
+    messages::DebuggerCommand,
+};
+
+/// Board state that pauses the simulation when it starts to hold for the debugged turmite.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BoardCondition {
+    /// The cell under the turmite holds this value.
+    Cell(u8),
+    /// The turmite is in this state.
+    State(u8),
+    /// The turmite is on this cell.
+    Position(UVec2),
+}
+
+impl Default for BoardCondition {
+    fn default() -> Self {
+        BoardCondition::Cell(0)
+    }
+}
+
+impl BoardCondition {
+    pub fn holds(&self, memory: &impl Cells, turmite: &Turmite) -> bool {
+        match *self {
+            BoardCondition::Cell(value) => memory.read(turmite.pos) == value,
+            BoardCondition::State(state) => turmite.state == state,
+            BoardCondition::Position(pos) => turmite.pos == pos,
+        }
+    }
+}
+
+impl fmt::Display for BoardCondition {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BoardCondition::Cell(value) => write!(f, "cell = {value}"),
+            BoardCondition::State(state) => write!(f, "state = {state}"),
+            BoardCondition::Position(pos) => write!(f, "position = ({}, {})", pos.x, pos.y),
+        }
+    }
+}
+
+/// Manual step asked for from the debugger panel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StepRequest {
+    /// Run one instruction.
+    Instruction,
+    /// Run until the turmite moves, ignoring breakpoints.
+    Move,
+}
+
+/// Program debugger attached to one turmite.
+///
+/// While the attached turmite has breakpoints it runs one instruction at a time, so the simulation can pause
+/// partway through a move. Pausing stops every turmite, and only the attached one can be stepped.
+#[derive(Resource, Clone, Default)]
+pub struct Debugger {
+    target: Option<Entity>,
+    paused: bool,
+    request: Option<StepRequest>,
+    breakpoints: BTreeSet<usize>,
+    /// Each condition, with whether it held before the last instruction.
+    conditions: Vec<(BoardCondition, bool)>,
+    /// Run the next instruction without checking breakpoints, so continuing from one doesn't stop again straight away.
+    skip_breakpoint: bool,
+    /// Why the simulation last paused.
+    reason: Option<String>,
+}
+
+impl Debugger {
+    // -- Getters --
+
+    #[inline]
+    pub fn target(&self) -> Option<Entity> {
+        self.target
+    }
+
+    #[inline]
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    #[inline]
+    pub fn breakpoints(&self) -> &BTreeSet<usize> {
+        &self.breakpoints
+    }
+
+    pub fn conditions(&self) -> impl Iterator<Item = &BoardCondition> {
+        self.conditions.iter().map(|(condition, _)| condition)
+    }
+
+    #[inline]
+    pub fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+
+    /// Whether `entity` should move this tick.
+    pub fn can_move(&self, entity: Entity) -> bool {
+        !self.paused || (self.target == Some(entity) && self.request.is_some())
+    }
+
+    // -- Controls --
+
+    /// Attach to a turmite, dropping breakpoints set on the previous one's program.
+    /// Detaching resumes the simulation, since nothing would be left to step it.
+    pub fn attach(&mut self, target: Option<Entity>) {
+        if target != self.target {
+            self.target = target;
+            self.breakpoints.clear();
+        }
+        if target.is_none() && self.paused {
+            self.resume();
+        }
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+        self.reason = Some("paused".to_string());
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+        self.skip_breakpoint = true;
+        self.reason = None;
+    }
+
+    /// Pause if running, then step the attached turmite on the next tick.
+    pub fn request_step(&mut self, step: StepRequest) {
+        self.paused = true;
+        self.request = Some(step);
+        self.reason = None;
+    }
+
+    pub fn toggle_breakpoint(&mut self, pc: usize) {
+        if !self.breakpoints.remove(&pc) {
+            self.breakpoints.insert(pc);
+        }
+    }
+
+    pub fn add_condition(&mut self, condition: BoardCondition) {
+        // Assume it held, so a condition that is already true waits until it next becomes true
+        self.conditions.push((condition, true));
+    }
+
+    pub fn remove_condition(&mut self, index: usize) {
+        self.conditions.remove(index);
+    }
+
+    pub fn apply(&mut self, command: DebuggerCommand) {
+        match command {
+            DebuggerCommand::Attach(target) => self.attach(target),
+            DebuggerCommand::Pause => self.pause(),
+            DebuggerCommand::Resume => self.resume(),
+            DebuggerCommand::Step(step) => self.request_step(step),
+            DebuggerCommand::ToggleBreakpoint(pc) => self.toggle_breakpoint(pc),
+            DebuggerCommand::AddCondition(condition) => self.add_condition(condition),
+            DebuggerCommand::RemoveCondition(index) => self.remove_condition(index),
+        }
+    }
+
+    // -- Execution --
+
+    /// Advance the attached turmite's program as the debugger allows, returning the step taken if it moved.
+    pub(crate) fn advance(
+        &mut self,
+        program: &mut TurmiteProgram,
+        memory: &mut impl Cells,
+        occupancy: &impl Crowd,
+        turmite: &mut Turmite,
+    ) -> Option<IVec2> {
+        let was_running = program.status == ProgramStatus::Running;
+        let moved = match self.request.take() {
+            Some(StepRequest::Instruction) => program.step_instruction(memory, occupancy, turmite),
+            Some(StepRequest::Move) => program.step(memory, occupancy, turmite),
+            None if self.breakpoints.is_empty() && self.conditions.is_empty() => program.step(memory, occupancy, turmite),
+            None => self.run_to_breakpoint(program, memory, occupancy, turmite),
+        };
+
+        // Keep conditions current, so stepping onto one doesn't fire it when the simulation continues
+        self.refresh_conditions(memory, turmite);
+        if was_running && program.status != ProgramStatus::Running {
+            self.paused = true;
+            self.reason = Some(format!("program {}", program.status));
+        }
+        moved
+    }
+
+    fn run_to_breakpoint(
+        &mut self,
+        program: &mut TurmiteProgram,
+        memory: &mut impl Cells,
+        occupancy: &impl Crowd,
+        turmite: &mut Turmite,
+    ) -> Option<IVec2> {
+        while program.status == ProgramStatus::Running {
+            if let Some(reason) = self.check_breakpoints(program, memory, turmite) {
+                self.paused = true;
+                self.reason = Some(reason);
+                return None;
+            }
+            if let Some(delta) = program.step_instruction(memory, occupancy, turmite) {
+                return Some(delta);
+            }
+        }
+        None
+    }
+
+    /// Reason to stop before the program's next instruction, if any.
+    fn check_breakpoints(&mut self, program: &TurmiteProgram, memory: &impl Cells, turmite: &Turmite) -> Option<String> {
+        let pc = program.vm.pc();
+        let mut reason = (self.breakpoints.contains(&pc) && !self.skip_breakpoint).then(|| format!("breakpoint at {pc}"));
+        self.skip_breakpoint = false;
+
+        for (condition, held) in &mut self.conditions {
+            let holds = condition.holds(memory, turmite);
+            if holds && !*held && reason.is_none() {
+                reason = Some(format!("{condition} at {pc}"));
+            }
+            *held = holds;
+        }
+        reason
+    }
+
+    fn refresh_conditions(&mut self, memory: &impl Cells, turmite: &Turmite) {
+        for (condition, held) in &mut self.conditions {
+            *held = condition.holds(memory, turmite);
+        }
+    }
+}
@@ -0,0 +1,86 @@
+use arc_vm::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    components::{ProgramStatus, Turmite, TurmiteProgram},
+    resources::{Memory, Swarm},
+};
+
+/// Zobrist key for a cell holding `value`, generated rather than looked up so the board needs no table.
+/// Empty cells have no key, so an empty board hashes to zero.
+#[inline]
+pub fn cell_key(index: usize, value: u8) -> u64 {
+    if value == 0 {
+        return 0;
+    }
+    mix(((index as u64) << 8) | value as u64)
+}
+
+/// Hash of `data` from scratch, as `Memory` keeps it incrementally.
+pub fn memory_hash(data: &[u8]) -> u64 {
+    data.iter()
+        .enumerate()
+        .fold(0, |hash, (index, &value)| hash ^ cell_key(index, value))
+}
+
+/// Hash of the board and every turmite on it, in order.
+/// Two equal hashes mean the whole simulation has, in all likelihood, come back to the same configuration.
+pub fn state_hash<'a>(
+    memory: &Memory,
+    turmites: impl IntoIterator<Item = (&'a Turmite, Option<&'a TurmiteProgram>)>,
+    swarm: &Swarm,
+) -> u64 {
+    let mut hash = memory.hash();
+    for (turmite, program) in turmites {
+        hash = combine(hash, turmite_key(turmite.pos(), turmite.state(), turmite.heading() as u8));
+        if let Some(program) = program {
+            hash = combine(hash, program_key(program));
+        }
+    }
+    for index in 0..swarm.len() {
+        let key = turmite_key(swarm.positions[index], swarm.states[index], swarm.headings[index] as u8);
+        hash = combine(hash, key);
+    }
+    hash
+}
+
+// -- Helpers --
+
+fn turmite_key(pos: UVec2, state: u8, heading: u8) -> u64 {
+    [pos.x as u64, pos.y as u64, state as u64, heading as u64]
+        .into_iter()
+        .fold(0, combine)
+}
+
+/// Key for the parts of a program's VM that decide what it does next, leaving out its counters.
+fn program_key(program: &TurmiteProgram) -> u64 {
+    let vm = program.vm();
+    let mut hash = combine(vm.pc() as u64, (*program.status() == ProgramStatus::Running) as u64);
+    for value in vm.stack() {
+        hash = match *value {
+            Value::Int(value) => combine(combine(hash, 0), value as u64),
+            Value::Bool(value) => combine(combine(hash, 1), value as u64),
+        };
+    }
+    for &frame in vm.frames() {
+        hash = combine(hash, frame as u64);
+    }
+    for &slot in vm.locals().iter().chain(vm.registers()) {
+        hash = combine(hash, slot as u64);
+    }
+    hash
+}
+
+#[inline]
+fn combine(hash: u64, value: u64) -> u64 {
+    mix(hash ^ value)
+}
+
+/// SplitMix64 finaliser, so keys are the same on every platform.
+#[inline]
+fn mix(x: u64) -> u64 {
+    let mut z = x.wrapping_add(0x9e37_79b9_7f4a_7c15);
+    z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+    z ^ (z >> 31)
+}
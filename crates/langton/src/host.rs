@@ -0,0 +1,145 @@
+use arc_vm::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    components::{ProgramStatus, Turmite, TurmiteProgram},
+    resources::{Memory, Occupancy},
+};
+
+/// Cells a turmite reads and writes: the board itself, or a speculative layer over it.
+pub trait Cells {
+    fn read(&self, coord: UVec2) -> u8;
+
+    fn write(&mut self, coord: UVec2, value: u8);
+}
+
+impl Cells for Memory {
+    #[inline]
+    fn read(&self, coord: UVec2) -> u8 {
+        Memory::read(self, coord)
+    }
+
+    #[inline]
+    fn write(&mut self, coord: UVec2, value: u8) {
+        Memory::write(self, coord, value);
+    }
+}
+
+/// Where turmites stand, as a stepping turmite sees it.
+pub trait Crowd {
+    fn is_occupied(&self, coord: UVec2) -> bool;
+
+    /// Record the stepping turmite moving from one cell to another.
+    fn relocate(&mut self, from: UVec2, to: UVec2);
+}
+
+impl Crowd for Occupancy {
+    #[inline]
+    fn is_occupied(&self, coord: UVec2) -> bool {
+        Occupancy::is_occupied(self, coord)
+    }
+
+    #[inline]
+    fn relocate(&mut self, from: UVec2, to: UVec2) {
+        Occupancy::relocate(self, from, to);
+    }
+}
+
+/// Connects a turmite's program to the board.
+pub struct BoardHost<'a, M: Cells = Memory, O: Crowd = Occupancy> {
+    pub memory: &'a mut M,
+    pub turmite: &'a mut Turmite,
+    /// Where every turmite stands, including this one.
+    pub occupancy: &'a O,
+    /// Unwrapped step taken by `MOVE`, if the program moved.
+    pub moved: Option<IVec2>,
+}
+
+impl<M: Cells, O: Crowd> Host for BoardHost<'_, M, O> {
+    fn read(&self) -> u8 {
+        self.memory.read(self.turmite.pos)
+    }
+
+    fn sense(&self, direction: Direction) -> u8 {
+        self.memory.read(self.neighbour(direction))
+    }
+
+    fn sense_turmite(&self, direction: Direction) -> bool {
+        self.occupancy.is_occupied(self.neighbour(direction))
+    }
+
+    fn write(&mut self, value: u8) {
+        self.memory.write(self.turmite.pos, value);
+    }
+
+    fn turn(&mut self, quarter_turns: i64) {
+        self.turmite.heading = self.turmite.heading.turned(quarter_turns);
+    }
+
+    fn advance(&mut self) {
+        let delta = self.turmite.heading.delta();
+        self.turmite.pos = Memory::wrap(self.turmite.pos.as_ivec2() + delta);
+        self.moved = Some(delta);
+    }
+
+    fn state(&self) -> u8 {
+        self.turmite.state
+    }
+
+    fn set_state(&mut self, state: u8) {
+        self.turmite.state = state;
+    }
+}
+
+impl<M: Cells, O: Crowd> BoardHost<'_, M, O> {
+    /// Cell next to the turmite in `direction`, relative to its heading.
+    fn neighbour(&self, direction: Direction) -> UVec2 {
+        let (forward, right) = direction.offset();
+        let heading = self.turmite.heading;
+        let offset = heading.delta() * forward as i32 + heading.turned(1).delta() * right as i32;
+        Memory::wrap(self.turmite.pos.as_ivec2() + offset)
+    }
+}
+
+impl TurmiteProgram {
+    /// Run the program until it moves the turmite, returning the step taken.
+    /// Returns `None` and stops the program if it halts, faults or runs into one of its limits,
+    /// which also bound how long this can take.
+    pub(crate) fn step(&mut self, memory: &mut impl Cells, occupancy: &impl Crowd, turmite: &mut Turmite) -> Option<IVec2> {
+        self.run(memory, occupancy, turmite, u64::MAX)
+    }
+
+    /// Run a single instruction, returning the step taken if it was `MOVE`.
+    pub(crate) fn step_instruction(
+        &mut self,
+        memory: &mut impl Cells,
+        occupancy: &impl Crowd,
+        turmite: &mut Turmite,
+    ) -> Option<IVec2> {
+        self.run(memory, occupancy, turmite, 1)
+    }
+
+    fn run(&mut self, memory: &mut impl Cells, occupancy: &impl Crowd, turmite: &mut Turmite, budget: u64) -> Option<IVec2> {
+        if self.status != ProgramStatus::Running {
+            return None;
+        }
+
+        let mut host = BoardHost {
+            memory,
+            turmite,
+            occupancy,
+            moved: None,
+        };
+        self.status = match self.vm.resume_executable(&self.program, &mut host, budget) {
+            Ok(Exit::Moved) => return host.moved,
+            Ok(Exit::Halted) => ProgramStatus::Halted,
+            Ok(Exit::OutOfBudget) => return None,
+            Err(VmError::LimitExceeded { limit, .. }) => {
+                self.exceeded = Some(limit);
+                ProgramStatus::LimitExceeded(limit)
+            }
+            Err(err) => ProgramStatus::Faulted(err),
+        };
+        None
+    }
+}
@@ -0,0 +1,27 @@
+use arc_vm::prelude::*;
+use bevy::prelude::*;
+
+use crate::{
+    debugger::{BoardCondition, StepRequest},
+    resources::LimitPolicy,
+};
+
+/// A program-driven turmite ran into one of its VM limits, and `policy` has been applied to it.
+#[derive(Message, Clone, Copy, Debug)]
+pub struct TurmiteLimitExceeded {
+    pub entity: Entity,
+    pub limit: Limit,
+    pub policy: LimitPolicy,
+}
+
+/// Debugger control from the panel, applied between ticks so the simulation pauses and steps exactly where asked.
+#[derive(Message, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DebuggerCommand {
+    Attach(Option<Entity>),
+    Pause,
+    Resume,
+    Step(StepRequest),
+    ToggleBreakpoint(usize),
+    AddCondition(BoardCondition),
+    RemoveCondition(usize),
+}
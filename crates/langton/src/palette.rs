@@ -0,0 +1,185 @@
+use std::{
+    error::Error,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use bevy::prelude::*;
+
+/// Built-in colour schemes.
+/// Every scheme draws 0 as white and 1 as black, so two-colour turmites look the same in each.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaletteScheme {
+    /// Golden-angle hue ramp.
+    Classic,
+    /// Okabe-Ito colours, distinguishable under common forms of colour blindness.
+    ColourBlind,
+    /// Golden-ratio spaced grey levels.
+    Greyscale,
+}
+
+impl PaletteScheme {
+    pub const ALL: [PaletteScheme; 3] = [PaletteScheme::Classic, PaletteScheme::ColourBlind, PaletteScheme::Greyscale];
+
+    /// The scheme after this one, wrapping back to the first.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|scheme| *scheme == self).unwrap_or_default();
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    fn colour(self, value: u8) -> [u8; 4] {
+        const OKABE_ITO: [[u8; 3]; 7] = [
+            [0xe6, 0x9f, 0x00], // orange
+            [0x56, 0xb4, 0xe9], // sky blue
+            [0x00, 0x9e, 0x73], // bluish green
+            [0xf0, 0xe4, 0x42], // yellow
+            [0x00, 0x72, 0xb2], // blue
+            [0xd5, 0x5e, 0x00], // vermillion
+            [0xcc, 0x79, 0xa7], // reddish purple
+        ];
+
+        match (self, value) {
+            (_, 0) => [255, 255, 255, 255],
+            (_, 1) => [0, 0, 0, 255],
+            (PaletteScheme::Classic, _) => {
+                let colour = Color::hsl((value as f32 * 137.508) % 360.0, 0.7, 0.5).to_srgba();
+                [
+                    (colour.red * 255.0) as u8,
+                    (colour.green * 255.0) as u8,
+                    (colour.blue * 255.0) as u8,
+                    255,
+                ]
+            }
+            (PaletteScheme::ColourBlind, _) => {
+                let [r, g, b] = OKABE_ITO[(value as usize - 2) % OKABE_ITO.len()];
+                [r, g, b, 255]
+            }
+            (PaletteScheme::Greyscale, _) => {
+                let level = ((value as f32 * 0.618_034).fract() * 0.8 + 0.1) * 255.0;
+                [level as u8, level as u8, level as u8, 255]
+            }
+        }
+    }
+}
+
+/// Precomputed packed RGBA8 colour for every cell value.
+#[derive(Resource, Clone)]
+pub struct Palette {
+    scheme: Option<PaletteScheme>,
+    colours: [u32; 256],
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self::from_scheme(PaletteScheme::Classic)
+    }
+}
+
+impl Palette {
+    pub fn from_scheme(scheme: PaletteScheme) -> Self {
+        Self {
+            scheme: Some(scheme),
+            colours: std::array::from_fn(|value| u32::from_ne_bytes(scheme.colour(value as u8))),
+        }
+    }
+
+    /// Build a palette from a list of colours.
+    /// Values past the end of the list cycle back through it.
+    pub fn from_colours(colours: &[[u8; 4]]) -> Option<Self> {
+        if colours.is_empty() {
+            return None;
+        }
+        Some(Self {
+            scheme: None,
+            colours: std::array::from_fn(|value| u32::from_ne_bytes(colours[value % colours.len()])),
+        })
+    }
+
+    /// Load a palette file.
+    ///
+    /// Each non-blank line is a hex colour, `RRGGBB` or `RRGGBBAA` with an optional leading `#`,
+    /// giving the colour of the next cell value starting from 0. Anything after a `;` is a comment.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, PaletteError> {
+        Self::parse(&fs::read_to_string(path)?)
+    }
+
+    pub fn parse(src: &str) -> Result<Self, PaletteError> {
+        let mut colours = Vec::new();
+        for (index, line) in src.lines().enumerate() {
+            let code = line.split(';').next().unwrap_or_default().trim();
+            if code.is_empty() {
+                continue;
+            }
+            if colours.len() == 256 {
+                return Err(PaletteError::TooManyColours { line: index + 1 });
+            }
+            let colour = parse_hex_colour(code).ok_or_else(|| PaletteError::InvalidColour {
+                line: index + 1,
+                text: code.to_string(),
+            })?;
+            colours.push(colour);
+        }
+        Self::from_colours(&colours).ok_or(PaletteError::Empty)
+    }
+
+    // -- Getters --
+
+    /// Built-in scheme this palette was generated from, or `None` if it was loaded.
+    #[inline]
+    pub fn scheme(&self) -> Option<PaletteScheme> {
+        self.scheme
+    }
+
+    #[inline(always)]
+    pub fn colour(&self, value: u8) -> u32 {
+        self.colours[value as usize]
+    }
+
+    #[inline]
+    pub fn colours(&self) -> &[u32; 256] {
+        &self.colours
+    }
+}
+
+/// Palette file to draw with instead of a built-in scheme, loaded at startup and again on `RELOAD_PALETTE`.
+#[derive(Resource, Clone, Default)]
+pub struct PaletteFile(pub Option<PathBuf>);
+
+#[derive(Debug)]
+pub enum PaletteError {
+    Io(io::Error),
+    InvalidColour { line: usize, text: String },
+    TooManyColours { line: usize },
+    Empty,
+}
+
+impl fmt::Display for PaletteError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PaletteError::Io(err) => write!(f, "{err}"),
+            PaletteError::InvalidColour { line, text } => write!(f, "line {line}: invalid colour `{text}`"),
+            PaletteError::TooManyColours { line } => write!(f, "line {line}: palettes hold at most 256 colours"),
+            PaletteError::Empty => write!(f, "palette has no colours"),
+        }
+    }
+}
+
+impl Error for PaletteError {}
+
+impl From<io::Error> for PaletteError {
+    fn from(err: io::Error) -> Self {
+        PaletteError::Io(err)
+    }
+}
+
+// -- Helpers --
+
+fn parse_hex_colour(text: &str) -> Option<[u8; 4]> {
+    let hex = text.strip_prefix('#').unwrap_or(text);
+    if !hex.is_ascii() || !(hex.len() == 6 || hex.len() == 8) {
+        return None;
+    }
+    let channel = |index: usize| u8::from_str_radix(&hex[index * 2..index * 2 + 2], 16).ok();
+    let alpha = if hex.len() == 8 { channel(3)? } else { 255 };
+    Some([channel(0)?, channel(1)?, channel(2)?, alpha])
+}
@@ -0,0 +1,166 @@
+use std::thread;
+
+use bevy::{platform::collections::HashMap, prelude::*};
+
+use crate::{
+    debugger::Debugger,
+    host::{Cells, Crowd},
+    resources::{Memory, Occupancy},
+    settings::{BOARD_SIZE, REGION_SIZE},
+    simulation::{Step, TickTurmite, step_turmite},
+};
+
+/// Set of `REGION_SIZE` square regions of the board.
+#[derive(Clone)]
+pub(crate) struct Regions {
+    bits: Vec<u64>,
+}
+
+impl Default for Regions {
+    fn default() -> Self {
+        Self {
+            bits: vec![0; (Self::GRID.x * Self::GRID.y).div_ceil(u64::BITS) as usize],
+        }
+    }
+}
+
+impl Regions {
+    const GRID: UVec2 = UVec2::new(BOARD_SIZE.x.div_ceil(REGION_SIZE), BOARD_SIZE.y.div_ceil(REGION_SIZE));
+
+    pub fn is_disjoint(&self, other: &Regions) -> bool {
+        self.bits.iter().zip(&other.bits).all(|(a, b)| a & b == 0)
+    }
+
+    // -- Updates --
+
+    /// Add every region holding `coord` or one of its eight neighbours, which covers all a turmite there can see.
+    pub fn mark_around(&mut self, coord: UVec2) {
+        for dy in -1..=1 {
+            for dx in -1..=1 {
+                let region = Memory::wrap(coord.as_ivec2() + IVec2::new(dx, dy)) / REGION_SIZE;
+                let index = region.y * Self::GRID.x + region.x;
+                self.bits[(index / u64::BITS) as usize] |= 1 << (index % u64::BITS);
+            }
+        }
+    }
+
+    pub fn union_with(&mut self, other: &Regions) {
+        for (a, b) in self.bits.iter_mut().zip(&other.bits) {
+            *a |= b;
+        }
+    }
+}
+
+/// A turmite's tick, stepped on its own against the board as it was at the start of the tick.
+pub(crate) struct Speculation {
+    /// The turmite and its program after the tick.
+    pub tick: TickTurmite,
+    /// Final value of every cell it wrote.
+    pub writes: HashMap<UVec2, u8>,
+    pub steps: Vec<Step>,
+    /// Regions it could have seen, and so the only ones where others stepping first could have changed its run.
+    pub footprint: Regions,
+}
+
+/// Step every turmite speculatively, spread evenly over `threads` threads, returning their runs in order.
+pub(crate) fn speculate(
+    turmites: &[TickTurmite],
+    debugger: &Debugger,
+    memory: &Memory,
+    occupancy: &Occupancy,
+    threads: usize,
+    steps_per_tick: usize,
+) -> Vec<Speculation> {
+    let chunk_size = turmites.len().div_ceil(threads).max(1);
+    thread::scope(|scope| {
+        let handles: Vec<_> = turmites
+            .chunks(chunk_size)
+            .map(|chunk| {
+                scope.spawn(move || {
+                    let mut debugger = debugger.clone();
+                    chunk
+                        .iter()
+                        .map(|tick| speculate_one(tick, &mut debugger, memory, occupancy, steps_per_tick))
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("speculative stepping panicked"))
+            .collect()
+    })
+}
+
+fn speculate_one(
+    tick: &TickTurmite,
+    debugger: &mut Debugger,
+    memory: &Memory,
+    occupancy: &Occupancy,
+    steps_per_tick: usize,
+) -> Speculation {
+    let mut tick = tick.clone();
+    let start = tick.turmite.pos;
+    let mut layer = Layer {
+        base: memory,
+        writes: HashMap::default(),
+    };
+    let mut shadow = Shadow {
+        base: occupancy,
+        start,
+        pos: start,
+    };
+    let mut steps = Vec::new();
+    let mut footprint = Regions::default();
+
+    footprint.mark_around(start);
+    step_turmite(&mut tick, debugger, &mut layer, &mut shadow, steps_per_tick, |step| {
+        footprint.mark_around(step.coord);
+        steps.push(step);
+    });
+    footprint.mark_around(tick.turmite.pos);
+
+    Speculation {
+        tick,
+        writes: layer.writes,
+        steps,
+        footprint,
+    }
+}
+
+/// Cells written by one turmite, over the board as it was at the start of the tick.
+struct Layer<'a> {
+    base: &'a Memory,
+    writes: HashMap<UVec2, u8>,
+}
+
+impl Cells for Layer<'_> {
+    #[inline]
+    fn read(&self, coord: UVec2) -> u8 {
+        self.writes.get(&coord).copied().unwrap_or_else(|| self.base.read(coord))
+    }
+
+    #[inline]
+    fn write(&mut self, coord: UVec2, value: u8) {
+        self.writes.insert(coord, value);
+    }
+}
+
+/// Where turmites stood at the start of the tick, with the stepping turmite moved from `start` to `pos`.
+struct Shadow<'a> {
+    base: &'a Occupancy,
+    start: UVec2,
+    pos: UVec2,
+}
+
+impl Crowd for Shadow<'_> {
+    #[inline]
+    fn is_occupied(&self, coord: UVec2) -> bool {
+        self.base.count(coord) - u32::from(coord == self.start) + u32::from(coord == self.pos) > 0
+    }
+
+    #[inline]
+    fn relocate(&mut self, _from: UVec2, to: UVec2) {
+        self.pos = to;
+    }
+}
@@ -0,0 +1,173 @@
+use std::{
+    error::Error,
+    fmt, fs, io, mem,
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::SystemTime,
+};
+
+use arc_vm::{binary, prelude::*, text};
+use bevy::prelude::*;
+
+/// Bytecode file on disk, watched for changes while turmites run it.
+pub struct ProgramFile {
+    path: PathBuf,
+    tier: Tier,
+    modified: Option<SystemTime>,
+    program: Arc<Executable>,
+    error: Option<ProgramError>,
+}
+
+impl ProgramFile {
+    // -- Getters --
+
+    #[inline]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Latest version of the file that loaded successfully.
+    #[inline]
+    pub fn program(&self) -> &Arc<Executable> {
+        &self.program
+    }
+
+    /// Why the current contents of the file couldn't be used, in which case the previous version keeps running.
+    #[inline]
+    pub fn error(&self) -> Option<&ProgramError> {
+        self.error.as_ref()
+    }
+}
+
+/// Program files in use, reloaded when they change on disk.
+///
+/// A reloaded program replaces the old one in every turmite that was running it, from the next tick.
+#[derive(Resource, Default)]
+pub struct ProgramFiles {
+    files: Vec<ProgramFile>,
+    /// Old and new versions of programs reloaded since the last swap.
+    reloaded: Vec<(Arc<Executable>, Arc<Executable>)>,
+}
+
+impl ProgramFiles {
+    /// Load a program from `path` and start watching it, or return the program already loaded from there.
+    pub fn load(&mut self, path: impl AsRef<Path>, tier: Tier) -> Result<Arc<Executable>, ProgramError> {
+        let path = path.as_ref();
+        if let Some(file) = self.files.iter().find(|file| file.path == path) {
+            return Ok(file.program.clone());
+        }
+
+        let modified = modified_time(path);
+        let program = Arc::new(load_program(path, tier)?);
+        self.files.push(ProgramFile {
+            path: path.to_path_buf(),
+            tier,
+            modified,
+            program: program.clone(),
+            error: None,
+        });
+        Ok(program)
+    }
+
+    // -- Getters --
+
+    #[inline]
+    pub fn files(&self) -> &[ProgramFile] {
+        &self.files
+    }
+
+    // -- Reloading --
+
+    /// Reload every file modified since it was last read.
+    pub fn poll(&mut self) {
+        for file in &mut self.files {
+            let modified = modified_time(&file.path);
+            if modified == file.modified {
+                continue;
+            }
+            file.modified = modified;
+
+            match load_program(&file.path, file.tier) {
+                Ok(program) => {
+                    info!("Reloaded program {}", file.path.display());
+                    let program = Arc::new(program);
+                    let old = mem::replace(&mut file.program, program.clone());
+                    self.reloaded.push((old, program));
+                    file.error = None;
+                }
+                Err(err) => {
+                    warn!("Failed to reload program {}: {}", file.path.display(), err);
+                    file.error = Some(err);
+                }
+            }
+        }
+    }
+
+    /// Programs reloaded since the last call, as old and new versions, oldest first.
+    pub fn take_reloaded(&mut self) -> Vec<(Arc<Executable>, Arc<Executable>)> {
+        mem::take(&mut self.reloaded)
+    }
+}
+
+/// Read, verify and prepare a program stored as text or binary bytecode.
+pub fn load_program(path: impl AsRef<Path>, tier: Tier) -> Result<Executable, ProgramError> {
+    let bytes = fs::read(path)?;
+    let program = if bytes.starts_with(&binary::MAGIC) {
+        binary::decode(&bytes)?
+    } else {
+        let src = String::from_utf8(bytes).map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err))?;
+        text::parse(&src)?
+    };
+    Ok(program.verify()?.into_executable(tier))
+}
+
+#[derive(Debug)]
+pub enum ProgramError {
+    Io(io::Error),
+    Parse(ParseError),
+    Decode(DecodeError),
+    Verify(VerifyError),
+}
+
+impl fmt::Display for ProgramError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ProgramError::Io(err) => write!(f, "{err}"),
+            ProgramError::Parse(err) => write!(f, "{err}"),
+            ProgramError::Decode(err) => write!(f, "{err}"),
+            ProgramError::Verify(err) => write!(f, "{err}"),
+        }
+    }
+}
+
+impl Error for ProgramError {}
+
+impl From<io::Error> for ProgramError {
+    fn from(err: io::Error) -> Self {
+        ProgramError::Io(err)
+    }
+}
+
+impl From<ParseError> for ProgramError {
+    fn from(err: ParseError) -> Self {
+        ProgramError::Parse(err)
+    }
+}
+
+impl From<DecodeError> for ProgramError {
+    fn from(err: DecodeError) -> Self {
+        ProgramError::Decode(err)
+    }
+}
+
+impl From<VerifyError> for ProgramError {
+    fn from(err: VerifyError) -> Self {
+        ProgramError::Verify(err)
+    }
+}
+
+// -- Helpers --
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|metadata| metadata.modified()).ok()
+}
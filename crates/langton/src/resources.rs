@@ -0,0 +1,740 @@
+use std::{collections::VecDeque, path::PathBuf, sync::Arc};
+
+use arc_vm::prelude::*;
+use bevy::{ecs::entity::EntityHashMap, math::I64Vec2, platform::collections::HashMap, prelude::*};
+use rand::{
+    Rng,
+    distr::{Bernoulli, Distribution, weighted::WeightedIndex},
+};
+
+use crate::{
+    components::{Heading, MARKER, coord_to_world_pos},
+    hash::cell_key,
+    settings::{BOARD_SIZE, PROGRAM_CALL_DEPTH, PROGRAM_FUEL, PROGRAM_STACK_DEPTH, PROGRAM_STEP_BUDGET, STATE_HISTORY_LEN},
+};
+
+#[derive(Resource, Clone)]
+pub struct Memory {
+    /// Cell values, in row-major order.
+    data: Vec<u8>,
+    hash: u64,
+}
+
+impl Default for Memory {
+    fn default() -> Self {
+        Self {
+            data: vec![0; BOARD_SIZE.element_product() as usize],
+            hash: 0,
+        }
+    }
+}
+
+impl Memory {
+    pub fn read(&self, coord: UVec2) -> u8 {
+        let index = coord.y as usize * BOARD_SIZE.x as usize + coord.x as usize;
+        self.data[index]
+    }
+
+    pub fn write(&mut self, coord: UVec2, value: u8) {
+        let index = coord.y as usize * BOARD_SIZE.x as usize + coord.x as usize;
+        self.hash ^= cell_key(index, self.data[index]) ^ cell_key(index, value);
+        self.data[index] = value;
+    }
+
+    /// Zobrist hash of every cell, kept up to date by `write`.
+    #[inline]
+    pub fn hash(&self) -> u64 {
+        self.hash
+    }
+
+    /// Cell values, in row-major order. Change them through `write`, which keeps the hash current.
+    #[inline]
+    pub fn data(&self) -> &[u8] {
+        &self.data
+    }
+
+    /// Cell at `coord`, wrapping around the edges of the board.
+    pub fn wrap(coord: IVec2) -> UVec2 {
+        coord.rem_euclid(BOARD_SIZE.as_ivec2()).as_uvec2()
+    }
+}
+
+/// How many turmites stand on each occupied cell.
+#[derive(Default)]
+pub struct Occupancy {
+    counts: HashMap<UVec2, u32>,
+}
+
+impl Occupancy {
+    pub fn is_occupied(&self, coord: UVec2) -> bool {
+        self.counts.contains_key(&coord)
+    }
+
+    #[inline]
+    pub fn count(&self, coord: UVec2) -> u32 {
+        self.counts.get(&coord).copied().unwrap_or(0)
+    }
+
+    // -- Updates --
+
+    pub fn rebuild(&mut self, positions: impl IntoIterator<Item = UVec2>) {
+        self.counts.clear();
+        for coord in positions {
+            *self.counts.entry(coord).or_default() += 1;
+        }
+    }
+
+    /// Record a turmite moving from one cell to another.
+    pub fn relocate(&mut self, from: UVec2, to: UVec2) {
+        if from == to {
+            return;
+        }
+        if let Some(count) = self.counts.get_mut(&from) {
+            *count -= 1;
+            if *count == 0 {
+                self.counts.remove(&from);
+            }
+        }
+        *self.counts.entry(to).or_default() += 1;
+    }
+}
+
+/// Cells stepped on since the canvas was last drawn, each listed once however often it changed.
+#[derive(Resource)]
+pub struct DirtyCells {
+    marked: Vec<bool>,
+    cells: Vec<UVec2>,
+    min: UVec2,
+    max: UVec2,
+}
+
+impl Default for DirtyCells {
+    fn default() -> Self {
+        Self {
+            marked: vec![false; BOARD_SIZE.element_product() as usize],
+            cells: Vec::new(),
+            min: UVec2::MAX,
+            max: UVec2::ZERO,
+        }
+    }
+}
+
+impl DirtyCells {
+    pub fn mark(&mut self, coord: UVec2) {
+        let index = coord.y as usize * BOARD_SIZE.x as usize + coord.x as usize;
+        if !self.marked[index] {
+            self.marked[index] = true;
+            self.cells.push(coord);
+            self.min = self.min.min(coord);
+            self.max = self.max.max(coord);
+        }
+    }
+
+    // -- Getters --
+
+    #[inline]
+    pub fn cells(&self) -> &[UVec2] {
+        &self.cells
+    }
+
+    /// Start and size of the smallest rect holding every dirty cell, ignoring wrapping.
+    pub fn bounds(&self) -> Option<(UVec2, UVec2)> {
+        (!self.cells.is_empty()).then(|| (self.min, self.max - self.min + UVec2::ONE))
+    }
+
+    // -- Updates --
+
+    pub fn clear(&mut self) {
+        for coord in self.cells.drain(..) {
+            self.marked[coord.y as usize * BOARD_SIZE.x as usize + coord.x as usize] = false;
+        }
+        self.min = UVec2::MAX;
+        self.max = UVec2::ZERO;
+    }
+}
+
+/// Per-cell count of how many times a turmite has stepped off each cell, indexed like `Memory`.
+#[derive(Resource)]
+pub struct VisitCounts {
+    data: Vec<u32>,
+    max: u32,
+}
+
+impl Default for VisitCounts {
+    fn default() -> Self {
+        Self {
+            data: vec![0; BOARD_SIZE.element_product() as usize],
+            max: 0,
+        }
+    }
+}
+
+impl VisitCounts {
+    pub fn read(&self, coord: UVec2) -> u32 {
+        let index = coord.y as usize * BOARD_SIZE.x as usize + coord.x as usize;
+        self.data[index]
+    }
+
+    /// Count one more visit to `coord`, returning the new total.
+    pub fn increment(&mut self, coord: UVec2) -> u32 {
+        let index = coord.y as usize * BOARD_SIZE.x as usize + coord.x as usize;
+        let count = self.data[index].saturating_add(1);
+        self.data[index] = count;
+        self.max = self.max.max(count);
+        count
+    }
+
+    pub fn set(&mut self, coord: UVec2, count: u32) {
+        let index = coord.y as usize * BOARD_SIZE.x as usize + coord.x as usize;
+        self.data[index] = count;
+        self.max = self.max.max(count);
+    }
+
+    /// Highest visit count of any cell.
+    #[inline]
+    pub fn max(&self) -> u32 {
+        self.max
+    }
+
+    /// Visit counts, in row-major order. Change them through `increment` or `set`, which keep `max` current.
+    #[inline]
+    pub fn data(&self) -> &[u32] {
+        &self.data
+    }
+
+    /// Upper end of the heatmap colour scale.
+    /// Only changes when `max` passes a power of two, so the canvas rarely needs a full repaint.
+    #[inline]
+    pub fn scale(&self) -> u32 {
+        self.max.checked_next_power_of_two().unwrap_or(u32::MAX).max(1)
+    }
+}
+
+/// What the canvas shows for each cell.
+#[derive(Resource, Clone, Copy, Default, PartialEq, Eq, Debug)]
+pub enum RenderMode {
+    /// Current cell values.
+    #[default]
+    Cells,
+    /// Log-scaled visit counts.
+    Heatmap,
+}
+
+impl RenderMode {
+    pub fn toggled(self) -> Self {
+        match self {
+            RenderMode::Cells => RenderMode::Heatmap,
+            RenderMode::Heatmap => RenderMode::Cells,
+        }
+    }
+}
+
+/// Region of the board that is filled when seeding.
+/// Square and disc regions are centred on the board.
+#[derive(Clone, Copy, Default)]
+pub enum SeedRegion {
+    #[default]
+    Full,
+    Square {
+        size: u32,
+    },
+    Disc {
+        radius: u32,
+    },
+}
+
+impl SeedRegion {
+    /// Inclusive-exclusive bounding box of the region in board coords.
+    pub fn bounds(&self) -> (UVec2, UVec2) {
+        let centre = BOARD_SIZE / 2;
+        match *self {
+            SeedRegion::Full => (UVec2::ZERO, BOARD_SIZE),
+            SeedRegion::Square { size } => {
+                let size = size.min(BOARD_SIZE.min_element());
+                let min = centre - UVec2::splat(size / 2);
+                (min, min + UVec2::splat(size))
+            }
+            SeedRegion::Disc { radius } => {
+                let radius = radius.min(BOARD_SIZE.min_element() / 2);
+                (
+                    centre - UVec2::splat(radius),
+                    (centre + UVec2::splat(radius + 1)).min(BOARD_SIZE),
+                )
+            }
+        }
+    }
+
+    pub fn contains(&self, coord: UVec2) -> bool {
+        let (min, max) = self.bounds();
+        if coord.cmplt(min).any() || coord.cmpge(max).any() {
+            return false;
+        }
+        match *self {
+            SeedRegion::Full | SeedRegion::Square { .. } => true,
+            SeedRegion::Disc { radius } => {
+                let delta = coord.as_i64vec2() - (BOARD_SIZE / 2).as_i64vec2();
+                delta.length_squared() <= radius as i64 * radius as i64
+            }
+        }
+    }
+}
+
+/// How each cell inside the seeded region picks its value.
+#[derive(Clone, Default)]
+pub enum SeedFill {
+    #[default]
+    Empty,
+    /// Each cell is set to `value` with probability `density`, otherwise left at zero.
+    Density { density: f64, value: u8 },
+    /// Each cell draws its value from the relative weights, indexed by colour.
+    Weights(Vec<f64>),
+}
+
+/// Initial board layout, drawn from `SeededRng` so a seed reproduces the same board.
+#[derive(Resource, Clone, Default)]
+pub struct BoardSeed {
+    pub region: SeedRegion,
+    pub fill: SeedFill,
+}
+
+impl BoardSeed {
+    pub fn uniform(density: f64) -> Self {
+        Self {
+            region: SeedRegion::Full,
+            fill: SeedFill::Density { density, value: 1 },
+        }
+    }
+
+    pub fn square(size: u32, density: f64) -> Self {
+        Self {
+            region: SeedRegion::Square { size },
+            fill: SeedFill::Density { density, value: 1 },
+        }
+    }
+
+    pub fn disc(radius: u32, density: f64) -> Self {
+        Self {
+            region: SeedRegion::Disc { radius },
+            fill: SeedFill::Density { density, value: 1 },
+        }
+    }
+
+    pub fn weighted(region: SeedRegion, weights: Vec<f64>) -> Self {
+        Self {
+            region,
+            fill: SeedFill::Weights(weights),
+        }
+    }
+
+    /// Fill `memory` inside the region, visiting cells in row-major order.
+    /// Returns the bounding box that was written, or `None` if nothing was seeded.
+    pub fn apply(&self, memory: &mut Memory, rng: &mut impl Rng) -> Option<(UVec2, UVec2)> {
+        let sampler = match &self.fill {
+            SeedFill::Empty => return None,
+            SeedFill::Density { density, value } => match Bernoulli::new(*density) {
+                Ok(bernoulli) => CellSampler::Density(bernoulli, *value),
+                Err(err) => {
+                    warn!("Invalid seed density {}: {}", density, err);
+                    return None;
+                }
+            },
+            SeedFill::Weights(weights) => {
+                if weights.len() > u8::MAX as usize + 1 {
+                    warn!("Seed weights cover {} colours, at most 256 are supported", weights.len());
+                    return None;
+                }
+                match WeightedIndex::new(weights) {
+                    Ok(index) => CellSampler::Weights(index),
+                    Err(err) => {
+                        warn!("Invalid seed weights: {}", err);
+                        return None;
+                    }
+                }
+            }
+        };
+
+        let (min, max) = self.region.bounds();
+        for y in min.y..max.y {
+            for x in min.x..max.x {
+                let coord = UVec2::new(x, y);
+                if self.region.contains(coord) {
+                    memory.write(coord, sampler.sample(rng));
+                }
+            }
+        }
+
+        Some((min, max))
+    }
+}
+
+enum CellSampler {
+    Density(Bernoulli, u8),
+    Weights(WeightedIndex<f64>),
+}
+
+impl CellSampler {
+    #[inline]
+    fn sample(&self, rng: &mut impl Rng) -> u8 {
+        match self {
+            CellSampler::Density(bernoulli, value) => {
+                if bernoulli.sample(rng) {
+                    *value
+                } else {
+                    0
+                }
+            }
+            CellSampler::Weights(index) => index.sample(rng) as u8,
+        }
+    }
+}
+
+/// Running totals for the simulation, updated incrementally as turmites step.
+#[derive(Resource, Clone)]
+pub struct SimulationStats {
+    steps: u64,
+    colour_counts: [u64; 256],
+    visited: Option<(UVec2, UVec2)>,
+    displacements: EntityHashMap<I64Vec2>,
+    instructions: u64,
+    limits_exceeded: [u64; Limit::ALL.len()],
+}
+
+impl Default for SimulationStats {
+    fn default() -> Self {
+        Self {
+            steps: 0,
+            colour_counts: [0; 256],
+            visited: None,
+            displacements: EntityHashMap::default(),
+            instructions: 0,
+            limits_exceeded: [0; Limit::ALL.len()],
+        }
+    }
+}
+
+impl SimulationStats {
+    /// Start counting from the current contents of `memory`.
+    pub fn from_memory(memory: &Memory) -> Self {
+        let mut stats = Self::default();
+        for &value in memory.data() {
+            stats.colour_counts[value as usize] += 1;
+        }
+        stats
+    }
+
+    // -- Getters --
+
+    /// Total number of turmite steps taken, summed over all turmites.
+    #[inline]
+    pub fn steps(&self) -> u64 {
+        self.steps
+    }
+
+    /// Number of cells currently holding each value.
+    #[inline]
+    pub fn colour_counts(&self) -> &[u64; 256] {
+        &self.colour_counts
+    }
+
+    #[inline]
+    pub fn colour_count(&self, value: u8) -> u64 {
+        self.colour_counts[value as usize]
+    }
+
+    /// Inclusive bounding box of every cell a turmite has stood on.
+    #[inline]
+    pub fn visited_bounds(&self) -> Option<(UVec2, UVec2)> {
+        self.visited
+    }
+
+    /// Net displacement of a turmite from where it was spawned, ignoring board wrapping.
+    #[inline]
+    pub fn displacement(&self, entity: Entity) -> Option<I64Vec2> {
+        self.displacements.get(&entity).copied()
+    }
+
+    #[inline]
+    pub fn displacements(&self) -> impl Iterator<Item = (Entity, I64Vec2)> + '_ {
+        self.displacements
+            .iter()
+            .map(|(entity, displacement)| (*entity, *displacement))
+    }
+
+    /// Total number of bytecode instructions run, summed over all program-driven turmites.
+    #[inline]
+    pub fn instructions(&self) -> u64 {
+        self.instructions
+    }
+
+    /// How many times each VM limit has stopped a turmite.
+    pub fn limits_exceeded(&self) -> impl Iterator<Item = (Limit, u64)> + '_ {
+        Limit::ALL.into_iter().zip(self.limits_exceeded.iter().copied())
+    }
+
+    // -- Updates --
+
+    #[inline]
+    pub fn record_instructions(&mut self, count: u64) {
+        self.instructions += count;
+    }
+
+    #[inline]
+    pub fn record_limit(&mut self, limit: Limit) {
+        self.limits_exceeded[limit as usize] += 1;
+    }
+
+    /// Record a single turmite step from `coord`, overwriting `input` with `output`.
+    #[inline]
+    pub fn record_step(&mut self, entity: Entity, coord: UVec2, delta: IVec2, input: u8, output: u8) {
+        self.record_swarm_step(coord, input, output);
+        *self.displacements.entry(entity).or_default() += delta.as_i64vec2();
+    }
+
+    /// Record a step by a swarm turmite, which has no displacement tracked of its own.
+    #[inline]
+    pub fn record_swarm_step(&mut self, coord: UVec2, input: u8, output: u8) {
+        self.steps += 1;
+
+        self.colour_counts[input as usize] -= 1;
+        self.colour_counts[output as usize] += 1;
+
+        self.visit(coord);
+    }
+
+    #[inline]
+    fn visit(&mut self, coord: UVec2) {
+        self.visited = Some(match self.visited {
+            None => (coord, coord),
+            Some((min, max)) => (min.min(coord), max.max(coord)),
+        });
+    }
+}
+
+/// What happens to a program-driven turmite that runs into one of its VM limits.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum LimitPolicy {
+    /// Leave the turmite where it is, no longer moving.
+    #[default]
+    Freeze,
+    /// Despawn the turmite.
+    Remove,
+    /// Restart its program from the first instruction with a fresh VM, keeping the turmite and board as they are.
+    Reset,
+}
+
+/// Sandbox for program-driven turmites, applied to each turmite's VM.
+#[derive(Resource, Clone, Copy, Debug)]
+pub struct ProgramLimits {
+    pub limits: Limits,
+    pub policy: LimitPolicy,
+}
+
+impl Default for ProgramLimits {
+    fn default() -> Self {
+        Self {
+            limits: Limits {
+                step_instructions: PROGRAM_STEP_BUDGET,
+                stack_depth: PROGRAM_STACK_DEPTH,
+                call_depth: PROGRAM_CALL_DEPTH,
+                fuel: PROGRAM_FUEL,
+            },
+            policy: LimitPolicy::default(),
+        }
+    }
+}
+
+/// Starting position, state and marker colour of a turmite.
+#[derive(Clone)]
+pub struct TurmiteSpawn {
+    pub pos: UVec2,
+    pub state: u8,
+    pub heading: Heading,
+    /// Marker colour, or `None` to pick one from the spawn index.
+    pub colour: Option<Color>,
+    /// Bytecode controller, compiled or interpreted, or `None` to follow the transition table.
+    pub program: Option<Arc<Executable>>,
+    /// Bytecode file to load and watch for changes, used in place of `program` unless it fails to load.
+    pub program_file: Option<PathBuf>,
+}
+
+impl Default for TurmiteSpawn {
+    fn default() -> Self {
+        Self {
+            pos: BOARD_SIZE / 2,
+            state: 0,
+            heading: Heading::North,
+            colour: None,
+            program: None,
+            program_file: None,
+        }
+    }
+}
+
+/// Turmites created at startup.
+#[derive(Resource, Clone)]
+pub struct TurmiteSpawns(pub Vec<TurmiteSpawn>);
+
+impl Default for TurmiteSpawns {
+    fn default() -> Self {
+        Self(vec![TurmiteSpawn::default()])
+    }
+}
+
+/// Table-driven turmites stored as parallel arrays rather than as entities, so runs of 100k or more stay cheap.
+#[derive(Resource, Clone, Default)]
+pub struct Swarm {
+    pub positions: Vec<UVec2>,
+    pub states: Vec<u8>,
+    pub headings: Vec<Heading>,
+}
+
+impl Swarm {
+    #[inline]
+    pub fn len(&self) -> usize {
+        self.positions.len()
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.positions.is_empty()
+    }
+
+    /// Corners of every turmite's marker, `scale` cells across and turned to its heading, as a triangle list.
+    pub fn marker_vertices(&self, scale: f32) -> Vec<[f32; 3]> {
+        let corners = Heading::ALL.map(|heading| MARKER.map(|corner| heading.rotation() * corner.extend(0.0) * scale));
+        let mut vertices = Vec::with_capacity(self.len() * MARKER.len());
+        for (&pos, &heading) in self.positions.iter().zip(&self.headings) {
+            let centre = coord_to_world_pos(pos);
+            vertices.extend(corners[heading as usize].map(|corner| (centre + corner).to_array()));
+        }
+        vertices
+    }
+
+    // -- Updates --
+
+    pub fn push(&mut self, pos: UVec2, state: u8, heading: Heading) {
+        self.positions.push(pos);
+        self.states.push(state);
+        self.headings.push(heading);
+    }
+}
+
+/// Swarm turmites scattered over a region at startup, drawn from `SeededRng` so a seed reproduces them.
+#[derive(Resource, Clone, Default)]
+pub struct SwarmSpawns {
+    pub count: usize,
+    pub region: SeedRegion,
+}
+
+impl SwarmSpawns {
+    pub fn new(count: usize) -> Self {
+        Self {
+            count,
+            region: SeedRegion::Full,
+        }
+    }
+
+    /// Place `count` turmites on random cells of the region, facing random headings.
+    /// A region with no cells, such as a square of size 0, gets no turmites.
+    pub fn apply(&self, rng: &mut impl Rng) -> Swarm {
+        let mut swarm = Swarm::default();
+        let (min, max) = self.region.bounds();
+        if min.cmpge(max).any() {
+            return swarm;
+        }
+        while swarm.len() < self.count {
+            let coord = UVec2::new(rng.random_range(min.x..max.x), rng.random_range(min.y..max.y));
+            if self.region.contains(coord) {
+                swarm.push(coord, 0, Heading::ALL[rng.random_range(0..Heading::ALL.len())]);
+            }
+        }
+        swarm
+    }
+}
+
+/// System hashes after each tick that stepped, to spot the simulation returning to an earlier configuration.
+/// Only the most recent `capacity` distinct hashes are kept, so cycles longer than that go unnoticed.
+#[derive(Resource)]
+pub struct StateHistory {
+    /// First recorded tick with each hash.
+    seen: HashMap<u64, u64>,
+    /// Hashes in `seen`, oldest first, to forget once there are more than `capacity`.
+    order: VecDeque<u64>,
+    capacity: usize,
+    ticks: u64,
+    latest: Option<u64>,
+    revisit: Option<Revisit>,
+}
+
+impl Default for StateHistory {
+    fn default() -> Self {
+        Self::with_capacity(STATE_HISTORY_LEN)
+    }
+}
+
+/// The first time the simulation came back to a configuration it had already been in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Revisit {
+    /// Recorded tick the configuration was first seen at.
+    pub first: u64,
+    /// Recorded tick it was seen again at.
+    pub again: u64,
+}
+
+impl StateHistory {
+    pub fn with_capacity(capacity: usize) -> Self {
+        Self {
+            seen: HashMap::default(),
+            order: VecDeque::new(),
+            capacity,
+            ticks: 0,
+            latest: None,
+            revisit: None,
+        }
+    }
+
+    // -- Getters --
+
+    #[inline]
+    pub fn latest(&self) -> Option<u64> {
+        self.latest
+    }
+
+    #[inline]
+    pub fn revisit(&self) -> Option<Revisit> {
+        self.revisit
+    }
+
+    // -- Updates --
+
+    /// Record the hash after a tick, returning the revisit if this is the first one.
+    pub fn record(&mut self, hash: u64) -> Option<Revisit> {
+        let tick = self.ticks;
+        self.ticks += 1;
+        self.latest = Some(hash);
+
+        // Only the first revisit is reported, so there's no need to keep remembering hashes after it
+        if self.revisit.is_some() {
+            return None;
+        }
+        let first = *self.seen.entry(hash).or_insert(tick);
+        if first == tick {
+            self.order.push_back(hash);
+            if self.order.len() > self.capacity
+                && let Some(oldest) = self.order.pop_front()
+            {
+                self.seen.remove(&oldest);
+            }
+            return None;
+        }
+        self.seen.clear();
+        self.order.clear();
+        self.revisit = Some(Revisit { first, again: tick });
+        self.revisit
+    }
+
+    /// Forget every hash and the revisit, for a board that has been reseeded.
+    pub fn clear(&mut self) {
+        *self = Self::with_capacity(self.capacity);
+    }
+}
@@ -0,0 +1,25 @@
+use arc_vm::prelude::Tier;
+use bevy::prelude::*;
+
+pub const BOARD_SIZE: UVec2 = UVec2::new(1024 * 4, 1024 * 4);
+
+pub const TICK_RATE: f64 = 64.0; // In hertz
+pub const STEPS_PER_TICK: usize = 10000;
+pub const SWARM_STEPS_PER_TICK: usize = 8; // Steps each swarm turmite takes per tick
+pub const REGION_SIZE: u32 = 64; // Side of the board regions used to tell apart turmites that can't affect each other
+pub const STATE_HISTORY_LEN: usize = 1 << 18; // Distinct state hashes remembered when looking for revisits
+pub const DIRTY_RECT_DENSITY: f32 = 0.5; // Share of a tick's dirty region that must have changed to redraw it whole
+pub const PROGRAM_STEP_BUDGET: u64 = 1024; // Instructions a program may run per step before it is stopped
+pub const PROGRAM_STACK_DEPTH: usize = 256;
+pub const PROGRAM_CALL_DEPTH: usize = 64;
+pub const PROGRAM_FUEL: u64 = u64::MAX; // Instructions a program may run over a turmite's life
+pub const PROGRAM_RELOAD_INTERVAL: f32 = 0.5; // Seconds between checks for changed program files
+pub const PROGRAM_FILE_TIER: Tier = Tier::Compiled; // How programs loaded from files are run
+
+pub const MARKER_MIN_PIXELS: f32 = 12.0; // Smallest on-screen marker size when zoomed out
+pub const MARKER_Z_INDEX: f32 = 1.0;
+pub const SWARM_MARKER_COLOUR: Color = Color::srgb(1.0, 0.35, 0.1);
+
+pub const TOGGLE_HEATMAP: KeyCode = KeyCode::KeyH;
+pub const CYCLE_PALETTE: KeyCode = KeyCode::KeyP;
+pub const RELOAD_PALETTE: KeyCode = KeyCode::KeyL;
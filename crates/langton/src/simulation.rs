@@ -0,0 +1,517 @@
+use std::{
+    error::Error,
+    fmt,
+    num::NonZero,
+    sync::{
+        Mutex,
+        mpsc::{self, Receiver, Sender, TryRecvError},
+    },
+    thread,
+};
+
+use bevy::prelude::*;
+
+use crate::{
+    components::{Heading, ProgramStatus, Turmite, TurmiteProgram},
+    debugger::Debugger,
+    hash::state_hash,
+    host::{Cells, Crowd},
+    parallel::{Regions, speculate},
+    resources::{DirtyCells, Memory, Occupancy, SimulationStats, Swarm, VisitCounts},
+    settings::{STEPS_PER_TICK, SWARM_STEPS_PER_TICK},
+};
+
+/// A turmite as handed to the simulation thread and back.
+#[derive(Clone)]
+pub struct TickTurmite {
+    pub entity: Entity,
+    pub turmite: Turmite,
+    pub program: Option<TurmiteProgram>,
+}
+
+/// Everything a tick needs from the world, handed to the simulation thread.
+struct TickJob {
+    turmites: Vec<TickTurmite>,
+    debugger: Debugger,
+    stats: SimulationStats,
+}
+
+/// A completed tick, published back to the world.
+pub struct TickResult {
+    pub turmites: Vec<TickTurmite>,
+    pub debugger: Debugger,
+    pub stats: SimulationStats,
+    pub cells: Vec<CellUpdate>,
+    /// Swarm turmites where the tick left them, or `None` if they didn't move.
+    pub swarm: Option<Swarm>,
+    /// Hash of the board and every turmite after the tick.
+    pub hash: u64,
+}
+
+/// New contents of a cell stepped on during a tick.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CellUpdate {
+    pub coord: UVec2,
+    pub value: u8,
+    pub visits: u32,
+}
+
+/// A single step of a turmite, off `coord`, overwriting `input` with `output`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Step {
+    pub coord: UVec2,
+    /// Unwrapped move taken, zero if the turmite stopped on the cell.
+    pub delta: IVec2,
+    pub input: u8,
+    pub output: u8,
+}
+
+/// Back buffer of the board, stepped by the simulation thread while the world draws from its own front copy.
+pub struct Board {
+    memory: Memory,
+    visits: VisitCounts,
+    occupancy: Occupancy,
+    dirty: DirtyCells,
+    swarm: Swarm,
+    /// Threads used to step turmites speculatively, or one to step them strictly in order.
+    threads: usize,
+    /// Most cells each turmite moves per tick.
+    steps_per_tick: usize,
+}
+
+impl Board {
+    pub fn new(memory: Memory) -> Self {
+        Self {
+            memory,
+            visits: VisitCounts::default(),
+            occupancy: Occupancy::default(),
+            dirty: DirtyCells::default(),
+            swarm: Swarm::default(),
+            threads: thread::available_parallelism().map_or(1, NonZero::get),
+            steps_per_tick: STEPS_PER_TICK,
+        }
+    }
+
+    pub fn with_swarm(mut self, swarm: Swarm) -> Self {
+        self.swarm = swarm;
+        self
+    }
+
+    pub fn with_threads(mut self, threads: usize) -> Self {
+        self.threads = threads.max(1);
+        self
+    }
+
+    pub fn with_steps_per_tick(mut self, steps: usize) -> Self {
+        self.steps_per_tick = steps;
+        self
+    }
+
+    // -- Getters --
+
+    #[inline]
+    pub fn memory(&self) -> &Memory {
+        &self.memory
+    }
+
+    #[inline]
+    pub fn visits(&self) -> &VisitCounts {
+        &self.visits
+    }
+
+    #[inline]
+    pub fn swarm(&self) -> &Swarm {
+        &self.swarm
+    }
+
+    #[inline]
+    pub fn threads(&self) -> usize {
+        self.threads
+    }
+
+    #[inline]
+    pub fn steps_per_tick(&self) -> usize {
+        self.steps_per_tick
+    }
+
+    /// Hash of the board, `turmites` and the swarm.
+    pub fn state_hash(&self, turmites: &[TickTurmite]) -> u64 {
+        let turmites = turmites.iter().map(|tick| (&tick.turmite, tick.program.as_ref()));
+        state_hash(&self.memory, turmites, &self.swarm)
+    }
+
+    // -- Updates --
+
+    /// Step each turmite the debugger lets move by up to `steps_per_tick` cells, in order, then each swarm turmite
+    /// by `SWARM_STEPS_PER_TICK`, returning every cell stepped on with its final value.
+    ///
+    /// With more than one thread, turmites are first stepped in parallel, each against the board as it was at the
+    /// start of the tick. A turmite's run is kept if it stayed clear of the regions every turmite before it touched,
+    /// and is otherwise stepped again in order, so the result is the same either way.
+    pub fn tick(
+        &mut self,
+        turmites: &mut [TickTurmite],
+        debugger: &mut Debugger,
+        stats: &mut SimulationStats,
+    ) -> Vec<CellUpdate> {
+        let positions = turmites.iter().map(|tick| tick.turmite.pos);
+        self.occupancy.rebuild(positions.chain(self.swarm.positions.iter().copied()));
+
+        // The debugged turmite can pause the simulation partway through the tick, stopping the turmites after it
+        if self.threads > 1 && turmites.len() > 1 && debugger.target().is_none() {
+            self.tick_parallel(turmites, debugger, stats);
+        } else {
+            for tick in turmites.iter_mut() {
+                self.step_in_order(tick, debugger, stats, None);
+            }
+        }
+        if !debugger.is_paused() {
+            self.step_swarm(stats);
+        }
+
+        let Self {
+            memory, visits, dirty, ..
+        } = self;
+        let cells = dirty
+            .cells()
+            .iter()
+            .map(|&coord| CellUpdate {
+                coord,
+                value: memory.read(coord),
+                visits: visits.read(coord),
+            })
+            .collect();
+        dirty.clear();
+        cells
+    }
+
+    fn tick_parallel(&mut self, turmites: &mut [TickTurmite], debugger: &mut Debugger, stats: &mut SimulationStats) {
+        let speculations = speculate(
+            turmites,
+            debugger,
+            &self.memory,
+            &self.occupancy,
+            self.threads,
+            self.steps_per_tick,
+        );
+
+        let mut touched = Regions::default();
+        for (tick, speculation) in turmites.iter_mut().zip(speculations) {
+            if !speculation.footprint.is_disjoint(&touched) {
+                self.step_in_order(tick, debugger, stats, Some(&mut touched));
+                continue;
+            }
+
+            // Nothing stepped before it this tick came near enough to change what it saw
+            for (&coord, &value) in &speculation.writes {
+                self.memory.write(coord, value);
+            }
+            self.occupancy.relocate(tick.turmite.pos, speculation.tick.turmite.pos);
+            for &step in &speculation.steps {
+                record(&mut self.visits, &mut self.dirty, stats, tick.entity, step);
+            }
+            touched.union_with(&speculation.footprint);
+            report_stop(tick.entity, is_running(tick), speculation.tick.program.as_ref());
+            *tick = speculation.tick;
+        }
+    }
+
+    /// Step the swarm from its arrays, skipping the per-turmite bookkeeping entity turmites need.
+    fn step_swarm(&mut self, stats: &mut SimulationStats) {
+        let Self {
+            memory,
+            visits,
+            occupancy,
+            dirty,
+            swarm,
+            ..
+        } = self;
+        for index in 0..swarm.len() {
+            let start = swarm.positions[index];
+            let mut pos = start;
+            let mut state = swarm.states[index];
+            for _ in 0..SWARM_STEPS_PER_TICK {
+                let input = memory.read(pos);
+                let (delta, new_state, output) = transition(state, input);
+                memory.write(pos, output);
+                visits.increment(pos);
+                dirty.mark(pos);
+                stats.record_swarm_step(pos, input, output);
+
+                if let Some(heading) = Heading::from_delta(delta) {
+                    swarm.headings[index] = heading;
+                }
+                state = new_state;
+                pos = Memory::wrap(pos.as_ivec2() + delta);
+            }
+            // Swarm turmites never sense, so others only need to see where each one ends up
+            occupancy.relocate(start, pos);
+            swarm.positions[index] = pos;
+            swarm.states[index] = state;
+        }
+    }
+
+    /// Step a turmite on the board itself, marking the regions it touches in `touched`.
+    fn step_in_order(
+        &mut self,
+        tick: &mut TickTurmite,
+        debugger: &mut Debugger,
+        stats: &mut SimulationStats,
+        mut touched: Option<&mut Regions>,
+    ) {
+        let Self {
+            memory,
+            visits,
+            occupancy,
+            dirty,
+            steps_per_tick,
+            ..
+        } = self;
+        let entity = tick.entity;
+        let was_running = is_running(tick);
+        if let Some(touched) = touched.as_deref_mut() {
+            touched.mark_around(tick.turmite.pos);
+        }
+        step_turmite(tick, debugger, memory, occupancy, *steps_per_tick, |step| {
+            if let Some(touched) = touched.as_deref_mut() {
+                touched.mark_around(step.coord);
+            }
+            record(visits, dirty, stats, entity, step);
+        });
+        if let Some(touched) = touched {
+            touched.mark_around(tick.turmite.pos);
+        }
+        report_stop(entity, was_running, tick.program.as_ref());
+        // Show anything written partway through a step the debugger paused in, before the step is recorded
+        if debugger.is_paused() {
+            dirty.mark(tick.turmite.pos);
+        }
+    }
+}
+
+/// Step a turmite by up to `steps` cells as the debugger allows, passing each step taken to `on_step`.
+pub(crate) fn step_turmite(
+    tick: &mut TickTurmite,
+    debugger: &mut Debugger,
+    memory: &mut impl Cells,
+    occupancy: &mut impl Crowd,
+    steps: usize,
+    mut on_step: impl FnMut(Step),
+) {
+    let TickTurmite {
+        entity,
+        turmite,
+        program,
+    } = tick;
+    let entity = *entity;
+    if !debugger.can_move(entity) {
+        return;
+    }
+    let debugged = debugger.target() == Some(entity);
+
+    for _ in 0..steps {
+        let coord = turmite.pos;
+        let input = match program.as_mut() {
+            // Partway through a step, the cell may already hold its new value
+            Some(program) => *program.step_input.get_or_insert_with(|| memory.read(coord)),
+            None => memory.read(coord),
+        };
+
+        // Move turmite, and update its state and memory
+        let moved = match program.as_mut() {
+            None => {
+                let (delta, new_state, output) = transition(turmite.state, input);
+                turmite.pos = Memory::wrap(coord.as_ivec2() + delta);
+                if let Some(heading) = Heading::from_delta(delta) {
+                    turmite.heading = heading;
+                }
+                turmite.state = new_state;
+                memory.write(coord, output);
+                Some(delta)
+            }
+            Some(program) if debugged => debugger.advance(program, memory, &*occupancy, turmite),
+            Some(program) => program.step(memory, &*occupancy, turmite),
+        };
+        occupancy.relocate(coord, turmite.pos);
+        let output = memory.read(coord);
+
+        // Paused partway through the step, which is recorded once the turmite moves
+        let stopped = program
+            .as_ref()
+            .is_some_and(|program| program.status != ProgramStatus::Running);
+        if moved.is_none() && !stopped {
+            break;
+        }
+        if let Some(program) = program.as_mut() {
+            program.step_input = None;
+        }
+
+        // A stopped program may still have written its final cell
+        if moved.is_none() && output == input {
+            break;
+        }
+
+        on_step(Step {
+            coord,
+            delta: moved.unwrap_or(IVec2::ZERO),
+            input,
+            output,
+        });
+
+        if moved.is_none() || debugger.is_paused() {
+            break;
+        }
+    }
+}
+
+/// Worker thread that runs ticks on its own `Board`, one at a time, so long ticks don't hold up the frame.
+#[derive(Resource)]
+pub struct SimulationThread {
+    jobs: Sender<TickJob>,
+    results: Mutex<Receiver<TickResult>>,
+    in_flight: bool,
+    /// Set once the thread has gone, after which no more ticks are dispatched.
+    stopped: bool,
+}
+
+impl SimulationThread {
+    /// Start the thread, handing it the board to step.
+    pub fn spawn(mut board: Board) -> Self {
+        let (jobs, job_rx) = mpsc::channel::<TickJob>();
+        let (result_tx, results) = mpsc::channel();
+        thread::Builder::new()
+            .name("simulation".to_string())
+            .spawn(move || {
+                // Stops once the world drops its end of either channel
+                for mut job in job_rx {
+                    let cells = board.tick(&mut job.turmites, &mut job.debugger, &mut job.stats);
+                    let hash = board.state_hash(&job.turmites);
+                    // The swarm steps every tick the debugger leaves running
+                    let swarm_moved = !board.swarm.is_empty() && !job.debugger.is_paused();
+                    let result = TickResult {
+                        turmites: job.turmites,
+                        debugger: job.debugger,
+                        stats: job.stats,
+                        cells,
+                        swarm: swarm_moved.then(|| board.swarm.clone()),
+                        hash,
+                    };
+                    if result_tx.send(result).is_err() {
+                        break;
+                    }
+                }
+            })
+            .expect("failed to spawn the simulation thread");
+
+        Self {
+            jobs,
+            results: Mutex::new(results),
+            in_flight: false,
+            stopped: false,
+        }
+    }
+
+    /// Whether no tick is running, so the world's turmites, stats and debugger are current.
+    #[inline]
+    pub fn is_idle(&self) -> bool {
+        !self.in_flight
+    }
+
+    /// Whether the thread has stopped, leaving the board as it was after the last collected tick.
+    #[inline]
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    // -- Updates --
+
+    pub(crate) fn dispatch(
+        &mut self,
+        turmites: Vec<TickTurmite>,
+        debugger: Debugger,
+        stats: SimulationStats,
+    ) -> Result<(), SimulationStopped> {
+        debug_assert!(self.is_idle(), "a tick is already running");
+        let job = TickJob {
+            turmites,
+            debugger,
+            stats,
+        };
+        if self.jobs.send(job).is_err() {
+            return Err(self.stop());
+        }
+        self.in_flight = true;
+        Ok(())
+    }
+
+    /// The running tick's result, if it has finished.
+    pub(crate) fn try_collect(&mut self) -> Result<Option<TickResult>, SimulationStopped> {
+        let received = match self.results.get_mut() {
+            Ok(results) => results.try_recv(),
+            Err(_) => Err(TryRecvError::Disconnected),
+        };
+        match received {
+            Ok(result) => {
+                self.in_flight = false;
+                Ok(Some(result))
+            }
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(self.stop()),
+        }
+    }
+
+    // -- Helpers --
+
+    fn stop(&mut self) -> SimulationStopped {
+        self.in_flight = false;
+        self.stopped = true;
+        SimulationStopped
+    }
+}
+
+/// The simulation thread has gone, having panicked partway through a tick, so no more ticks can run.
+#[derive(Debug)]
+pub struct SimulationStopped;
+
+impl fmt::Display for SimulationStopped {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "the simulation thread stopped unexpectedly")
+    }
+}
+
+impl Error for SimulationStopped {}
+
+// -- Helpers --
+
+fn record(visits: &mut VisitCounts, dirty: &mut DirtyCells, stats: &mut SimulationStats, entity: Entity, step: Step) {
+    visits.increment(step.coord);
+    dirty.mark(step.coord);
+    stats.record_step(entity, step.coord, step.delta, step.input, step.output);
+}
+
+fn is_running(tick: &TickTurmite) -> bool {
+    tick.program
+        .as_ref()
+        .is_some_and(|program| program.status == ProgramStatus::Running)
+}
+
+/// Warn about a program that was running and has now run into a limit or faulted.
+/// Only called once a run is kept, so a speculative run that is stepped again doesn't warn twice.
+fn report_stop(entity: Entity, was_running: bool, program: Option<&TurmiteProgram>) {
+    if !was_running {
+        return;
+    }
+    match program.map(|program| &program.status) {
+        Some(ProgramStatus::LimitExceeded(limit)) => warn!("Turmite {} ran into its {} limit", entity, limit),
+        Some(ProgramStatus::Faulted(err)) => warn!("Turmite {} program failed: {}", entity, err),
+        _ => {}
+    }
+}
+
+/// Langton's ant, keeping its heading as the state: on 0 turn right and paint the cell, and on any other colour turn
+/// left and clear it. States past the last heading wrap around, so every state and colour has a rule.
+fn transition(state: u8, input: u8) -> (IVec2, u8, u8) {
+    let painted = input != 0;
+    let heading = Heading::ALL[state as usize % Heading::ALL.len()].turned(if painted { -1 } else { 1 });
+    (heading.delta(), heading as u8, !painted as u8)
+}
@@ -0,0 +1,719 @@
+use std::sync::Arc;
+
+use arc_canvas::{
+    messages::{DrawIndex, DrawIndexRect},
+    palette::CanvasPalette,
+};
+use arc_random::resources::SeededRng;
+use bevy::{asset::RenderAssetUsages, camera::visibility::NoFrustumCulling, mesh::PrimitiveTopology, prelude::*};
+use bevy_egui::{EguiContexts, egui};
+
+use crate::{
+    components::{MARKER, SwarmMarkers, Turmite, TurmiteProgram, coord_to_world_pos},
+    debugger::{BoardCondition, Debugger, StepRequest},
+    messages::{DebuggerCommand, TurmiteLimitExceeded},
+    palette::{Palette, PaletteFile, PaletteScheme},
+    programs::ProgramFiles,
+    resources::{
+        BoardSeed, DirtyCells, LimitPolicy, Memory, ProgramLimits, RenderMode, Revisit, SimulationStats, StateHistory, Swarm,
+        SwarmSpawns, TurmiteSpawns, VisitCounts,
+    },
+    settings::{
+        BOARD_SIZE, CYCLE_PALETTE, DIRTY_RECT_DENSITY, MARKER_MIN_PIXELS, PROGRAM_FILE_TIER, PROGRAM_RELOAD_INTERVAL,
+        RELOAD_PALETTE, SWARM_MARKER_COLOUR, TOGGLE_HEATMAP,
+    },
+    simulation::{Board, SimulationThread, TickTurmite},
+};
+
+pub fn seed_board(
+    board_seed: Res<BoardSeed>,
+    mut seeded_rng: ResMut<SeededRng>,
+    mut memory: ResMut<Memory>,
+    mut history: ResMut<StateHistory>,
+    mut draw_index_rect_msg: MessageWriter<DrawIndexRect>,
+) {
+    history.clear();
+    let Some((min, max)) = board_seed.apply(&mut memory, seeded_rng.rng()) else {
+        return;
+    };
+    info!("Seeded board using seed {}", seeded_rng.seed());
+
+    // Repaint the seeded region
+    let size = max - min;
+    let mut indices = Vec::with_capacity(size.element_product() as usize);
+    for y in min.y..max.y {
+        for x in min.x..max.x {
+            indices.push(memory.read(UVec2::new(x, y)));
+        }
+    }
+    draw_index_rect_msg.write(DrawIndexRect {
+        start: min,
+        size,
+        indices,
+    });
+}
+
+pub fn init_stats(memory: Res<Memory>, mut stats: ResMut<SimulationStats>) {
+    *stats = SimulationStats::from_memory(&memory);
+}
+
+pub fn spawn_turmites(
+    mut commands: Commands,
+    spawns: Res<TurmiteSpawns>,
+    limits: Res<ProgramLimits>,
+    mut files: ResMut<ProgramFiles>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    let [a, b, c] = MARKER;
+    let marker = meshes.add(Triangle2d::new(a, b, c));
+
+    for (index, spawn) in spawns.0.iter().enumerate() {
+        let colour = spawn
+            .colour
+            .unwrap_or_else(|| Color::hsl((index as f32 * 137.508) % 360.0, 0.7, 0.5));
+
+        let mut turmite = commands.spawn((
+            Mesh2d(marker.clone()),
+            MeshMaterial2d(materials.add(colour)),
+            Turmite {
+                pos: spawn.pos,
+                state: spawn.state,
+                heading: spawn.heading,
+            },
+            Transform::from_translation(coord_to_world_pos(spawn.pos)).with_rotation(spawn.heading.rotation()),
+        ));
+        let program = match &spawn.program_file {
+            Some(path) => match files.load(path, PROGRAM_FILE_TIER) {
+                Ok(program) => Some(program),
+                Err(err) => {
+                    warn!("Failed to load program {}: {}", path.display(), err);
+                    spawn.program.clone()
+                }
+            },
+            None => spawn.program.clone(),
+        };
+        if let Some(program) = program {
+            turmite.insert(TurmiteProgram::new(program, limits.limits));
+        }
+    }
+}
+
+/// Start the simulation thread with its own copy of the seeded board.
+pub fn start_simulation(mut commands: Commands, memory: Res<Memory>, swarm: Res<Swarm>) {
+    let board = Board::new(memory.clone()).with_swarm(swarm.clone());
+    commands.insert_resource(SimulationThread::spawn(board));
+}
+
+/// Scatter the swarm, with one entity carrying all of its markers.
+pub fn spawn_swarm(
+    mut commands: Commands,
+    spawns: Res<SwarmSpawns>,
+    mut seeded_rng: ResMut<SeededRng>,
+    mut swarm: ResMut<Swarm>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<ColorMaterial>>,
+) {
+    *swarm = spawns.apply(seeded_rng.rng());
+    if swarm.is_empty() {
+        if spawns.count > 0 {
+            warn!("Swarm region holds no cells, so no swarm was spawned");
+        }
+        return;
+    }
+    info!("Spawned a swarm of {} turmites", swarm.len());
+
+    let mesh = Mesh::new(PrimitiveTopology::TriangleList, RenderAssetUsages::default())
+        .with_inserted_attribute(Mesh::ATTRIBUTE_POSITION, Vec::<[f32; 3]>::new());
+    commands.spawn((
+        Mesh2d(meshes.add(mesh)),
+        MeshMaterial2d(materials.add(SWARM_MARKER_COLOUR)),
+        Transform::default(),
+        // The mesh is rebuilt as the swarm moves, so bounds computed once would go stale
+        NoFrustumCulling,
+        SwarmMarkers,
+    ));
+}
+
+/// Apply the last tick from the simulation thread, once it has finished, to the turmites and the front board.
+/// Logs once if the thread has stopped, leaving the board frozen.
+#[allow(clippy::too_many_arguments)] // Bevy system parameters
+pub fn collect_tick(
+    mut simulation: ResMut<SimulationThread>,
+    mut memory: ResMut<Memory>,
+    mut visits: ResMut<VisitCounts>,
+    mut dirty: ResMut<DirtyCells>,
+    mut stats: ResMut<SimulationStats>,
+    mut debugger: ResMut<Debugger>,
+    mut swarm: ResMut<Swarm>,
+    mut history: ResMut<StateHistory>,
+    mut query: Query<(&mut Turmite, &mut Transform, Option<&mut TurmiteProgram>)>,
+) {
+    if simulation.is_stopped() {
+        return;
+    }
+    let result = match simulation.try_collect() {
+        Ok(Some(result)) => result,
+        Ok(None) => return,
+        Err(err) => {
+            error!("{err}, so the board will no longer change");
+            return;
+        }
+    };
+
+    for TickTurmite {
+        entity,
+        turmite,
+        program,
+    } in result.turmites
+    {
+        let Ok((mut current, mut transform, current_program)) = query.get_mut(entity) else {
+            continue;
+        };
+        transform.translation = coord_to_world_pos(turmite.pos);
+        transform.rotation = turmite.heading.rotation();
+        *current = turmite;
+        if let (Some(mut current_program), Some(program)) = (current_program, program) {
+            *current_program = program;
+        }
+    }
+    for cell in &result.cells {
+        memory.write(cell.coord, cell.value);
+        visits.set(cell.coord, cell.visits);
+        dirty.mark(cell.coord);
+    }
+    *stats = result.stats;
+    *debugger = result.debugger;
+    if let Some(moved) = result.swarm {
+        *swarm = moved;
+    }
+
+    // Ticks where nothing stepped, such as while paused, would all look like revisits
+    if !result.cells.is_empty()
+        && let Some(Revisit { first, again }) = history.record(result.hash)
+    {
+        info!(
+            "Simulation revisited its state from tick {first} at tick {again}, a cycle of {} ticks",
+            again - first
+        );
+    }
+}
+
+/// Hand the next tick to the simulation thread, unless the last one is still running or the thread has stopped.
+pub fn dispatch_tick(
+    mut simulation: ResMut<SimulationThread>,
+    debugger: Res<Debugger>,
+    stats: Res<SimulationStats>,
+    query: Query<(Entity, &Turmite, Option<&TurmiteProgram>)>,
+) {
+    if !simulation.is_idle() || simulation.is_stopped() {
+        return;
+    }
+    let turmites = query
+        .iter()
+        .map(|(entity, turmite, program)| TickTurmite {
+            entity,
+            turmite: turmite.clone(),
+            program: program.cloned(),
+        })
+        .collect();
+    if let Err(err) = simulation.dispatch(turmites, debugger.clone(), stats.clone()) {
+        error!("{err}, so the board will no longer change");
+    }
+}
+
+/// Whether the world's turmites, stats and debugger are safe to change, with no tick running.
+pub fn simulation_idle(simulation: Res<SimulationThread>) -> bool {
+    simulation.is_idle() && !simulation.is_stopped()
+}
+
+/// Apply commands from the debugger panel between ticks, so pausing and stepping land exactly where asked.
+pub fn apply_debugger_commands(
+    mut command_msg: MessageReader<DebuggerCommand>,
+    mut pending: Local<Vec<DebuggerCommand>>,
+    simulation: Res<SimulationThread>,
+    mut debugger: ResMut<Debugger>,
+) {
+    pending.extend(command_msg.read().copied());
+    if !simulation.is_idle() {
+        return;
+    }
+    for command in pending.drain(..) {
+        debugger.apply(command);
+    }
+}
+
+/// Keep markers at least `MARKER_MIN_PIXELS` wide on screen when zoomed out, and one cell wide when zoomed in.
+pub fn scale_turmite_markers(camera: Single<&Projection, With<Camera>>, mut query: Query<&mut Transform, With<Turmite>>) {
+    let Projection::Orthographic(ortho) = *camera else {
+        return;
+    };
+
+    let scale = marker_scale(ortho);
+    for mut transform in query.iter_mut() {
+        transform.scale = Vec3::splat(scale);
+    }
+}
+
+/// Rebuild the swarm's marker mesh once a tick has moved it, or when zooming changes the marker size.
+pub fn draw_swarm_markers(
+    camera: Single<&Projection, With<Camera>>,
+    swarm: Res<Swarm>,
+    mut drawn_scale: Local<f32>,
+    mut meshes: ResMut<Assets<Mesh>>,
+    markers: Query<&Mesh2d, With<SwarmMarkers>>,
+) {
+    let Projection::Orthographic(ortho) = *camera else {
+        return;
+    };
+    let Ok(handle) = markers.single() else {
+        return;
+    };
+    let scale = marker_scale(ortho);
+    if !swarm.is_changed() && scale == *drawn_scale {
+        return;
+    }
+    let Some(mesh) = meshes.get_mut(&handle.0) else {
+        return;
+    };
+    *drawn_scale = scale;
+    mesh.insert_attribute(Mesh::ATTRIBUTE_POSITION, swarm.marker_vertices(scale));
+}
+
+pub fn toggle_render_mode(keys: Res<ButtonInput<KeyCode>>, mut render_mode: ResMut<RenderMode>) {
+    if keys.just_pressed(TOGGLE_HEATMAP) {
+        *render_mode = render_mode.toggled();
+    }
+}
+
+pub fn cycle_palette(keys: Res<ButtonInput<KeyCode>>, mut palette: ResMut<Palette>) {
+    if keys.just_pressed(CYCLE_PALETTE) {
+        let scheme = palette.scheme().map_or(PaletteScheme::Classic, PaletteScheme::next);
+        *palette = Palette::from_scheme(scheme);
+    }
+}
+
+/// Draw with the palette file, if one is set, before the board is first painted.
+pub fn load_palette_file(file: Res<PaletteFile>, mut palette: ResMut<Palette>) {
+    read_palette_file(&file, &mut palette);
+}
+
+/// Reload the palette file, so edits to it show without restarting.
+pub fn reload_palette_file(keys: Res<ButtonInput<KeyCode>>, file: Res<PaletteFile>, mut palette: ResMut<Palette>) {
+    if keys.just_pressed(RELOAD_PALETTE) {
+        read_palette_file(&file, &mut palette);
+    }
+}
+
+/// Check the program files for changes every `PROGRAM_RELOAD_INTERVAL` seconds.
+pub fn poll_program_files(time: Res<Time>, mut since_poll: Local<f32>, mut files: ResMut<ProgramFiles>) {
+    *since_poll += time.delta_secs();
+    if *since_poll < PROGRAM_RELOAD_INTERVAL {
+        return;
+    }
+    *since_poll = 0.0;
+    files.poll();
+}
+
+/// Start every turmite running a reloaded program on its new version.
+pub fn swap_reloaded_programs(mut files: ResMut<ProgramFiles>, mut query: Query<&mut TurmiteProgram>) {
+    for (old, new) in files.take_reloaded() {
+        for mut program in &mut query {
+            if Arc::ptr_eq(program.program(), &old) {
+                program.replace(new.clone());
+            }
+        }
+    }
+}
+
+pub fn apply_program_limits(limits: Res<ProgramLimits>, mut query: Query<&mut TurmiteProgram>) {
+    if !limits.is_changed() {
+        return;
+    }
+    for mut program in &mut query {
+        program.vm.set_limits(limits.limits);
+    }
+}
+
+pub fn enforce_program_limits(
+    mut commands: Commands,
+    limits: Res<ProgramLimits>,
+    mut stats: ResMut<SimulationStats>,
+    mut limit_msg: MessageWriter<TurmiteLimitExceeded>,
+    mut query: Query<(Entity, &mut TurmiteProgram)>,
+) {
+    for (entity, mut program) in &mut query {
+        stats.record_instructions(program.take_executed());
+        let Some(limit) = program.exceeded.take() else {
+            continue;
+        };
+
+        stats.record_limit(limit);
+        match limits.policy {
+            LimitPolicy::Freeze => {}
+            LimitPolicy::Remove => commands.entity(entity).despawn(),
+            LimitPolicy::Reset => program.restart(),
+        }
+        limit_msg.write(TurmiteLimitExceeded {
+            entity,
+            limit,
+            policy: limits.policy,
+        });
+    }
+}
+
+/// Colour the canvas with the cell palette, or with the heat ramp while drawing the heatmap.
+pub fn update_canvas_palette(render_mode: Res<RenderMode>, palette: Res<Palette>, mut canvas_palette: ResMut<CanvasPalette>) {
+    if !render_mode.is_changed() && !palette.is_changed() {
+        return;
+    }
+    let colours = match *render_mode {
+        RenderMode::Cells => *palette.colours(),
+        RenderMode::Heatmap => std::array::from_fn(|index| heat_colour(index as u8)),
+    };
+    canvas_palette.set_if_neq(CanvasPalette::new(&colours));
+}
+
+/// Repaint the whole canvas when the render mode changes, or when the heatmap scale grows.
+/// The canvas holds cell values rather than colours, so changing the palette needs no repaint.
+pub fn repaint_canvas(
+    mut draw_index_rect_msg: MessageWriter<DrawIndexRect>,
+    mut painted: Local<Option<(RenderMode, u32)>>,
+    render_mode: Res<RenderMode>,
+    memory: Res<Memory>,
+    visits: Res<VisitCounts>,
+) {
+    let current = (*render_mode, visits.scale());
+    let Some(previous) = painted.replace(current) else {
+        // The board was painted when it was seeded
+        return;
+    };
+    let stale = match *render_mode {
+        RenderMode::Cells => previous.0 != RenderMode::Cells,
+        RenderMode::Heatmap => previous != current,
+    };
+    if !stale {
+        return;
+    }
+
+    let indices = match *render_mode {
+        RenderMode::Cells => memory.data().to_vec(),
+        RenderMode::Heatmap => visits.data().iter().map(|&count| heat_index(count, current.1)).collect(),
+    };
+    draw_index_rect_msg.write(DrawIndexRect {
+        start: UVec2::ZERO,
+        size: BOARD_SIZE,
+        indices,
+    });
+}
+
+/// Draw the cells stepped on this tick with their final values, as one rect when they are dense enough.
+pub fn draw_dirty_cells(
+    mut draw_index_msg: MessageWriter<DrawIndex>,
+    mut draw_index_rect_msg: MessageWriter<DrawIndexRect>,
+    render_mode: Res<RenderMode>,
+    memory: Res<Memory>,
+    visits: Res<VisitCounts>,
+    mut dirty: ResMut<DirtyCells>,
+) {
+    let Some((start, size)) = dirty.bounds() else {
+        return;
+    };
+    let index = |coord: UVec2| match *render_mode {
+        RenderMode::Cells => memory.read(coord),
+        RenderMode::Heatmap => heat_index(visits.read(coord), visits.scale()),
+    };
+
+    if dirty.cells().len() as f32 >= size.element_product() as f32 * DIRTY_RECT_DENSITY {
+        let mut indices = Vec::with_capacity(size.element_product() as usize);
+        for y in start.y..start.y + size.y {
+            for x in start.x..start.x + size.x {
+                indices.push(index(UVec2::new(x, y)));
+            }
+        }
+        draw_index_rect_msg.write(DrawIndexRect { start, size, indices });
+    } else {
+        draw_index_msg.write_batch(dirty.cells().iter().map(|&pos| DrawIndex { pos, index: index(pos) }));
+    }
+    dirty.clear();
+}
+
+pub fn show_stats_panel(
+    mut contexts: EguiContexts,
+    stats: Res<SimulationStats>,
+    history: Res<StateHistory>,
+    simulation: Res<SimulationThread>,
+) -> Result {
+    egui::Window::new("Statistics").show(contexts.ctx_mut()?, |ui| {
+        if simulation.is_stopped() {
+            ui.colored_label(egui::Color32::RED, "Simulation thread stopped, so the board is frozen");
+        }
+        ui.label(format!("Steps: {}", stats.steps()));
+        if let Some(hash) = history.latest() {
+            ui.label(format!("State hash: {hash:016x}"));
+        }
+        if let Some(Revisit { first, again }) = history.revisit() {
+            ui.label(format!("Revisited tick {first} at tick {again}"));
+        }
+        ui.label(format!("Instructions: {}", stats.instructions()));
+        for (limit, count) in stats.limits_exceeded().filter(|(_, count)| *count > 0) {
+            ui.label(format!("Stopped by {limit} limit: {count}"));
+        }
+
+        match stats.visited_bounds() {
+            Some((min, max)) => ui.label(format!(
+                "Visited: ({}, {}) to ({}, {}), {} x {}",
+                min.x,
+                min.y,
+                max.x,
+                max.y,
+                max.x - min.x + 1,
+                max.y - min.y + 1
+            )),
+            None => ui.label("Visited: none"),
+        };
+
+        ui.separator();
+        egui::Grid::new("colour_counts").striped(true).show(ui, |ui| {
+            ui.strong("Colour");
+            ui.strong("Cells");
+            ui.end_row();
+            for (value, count) in stats.colour_counts().iter().enumerate().filter(|(_, count)| **count > 0) {
+                ui.label(value.to_string());
+                ui.label(count.to_string());
+                ui.end_row();
+            }
+        });
+
+        ui.separator();
+        egui::Grid::new("displacements").striped(true).show(ui, |ui| {
+            ui.strong("Turmite");
+            ui.strong("Displacement");
+            ui.end_row();
+            for (entity, displacement) in stats.displacements() {
+                ui.label(entity.to_string());
+                ui.label(format!("({}, {})", displacement.x, displacement.y));
+                ui.end_row();
+            }
+        });
+    });
+    Ok(())
+}
+
+/// Disassembly, stack and breakpoints for the turmite the debugger is attached to.
+pub fn show_debugger_panel(
+    mut contexts: EguiContexts,
+    debugger: Res<Debugger>,
+    mut command_msg: MessageWriter<DebuggerCommand>,
+    mut draft: Local<BoardCondition>,
+    query: Query<(Entity, &Turmite, &TurmiteProgram)>,
+) -> Result {
+    egui::Window::new("Debugger").show(contexts.ctx_mut()?, |ui| {
+        let mut target = debugger.target().filter(|&entity| query.contains(entity));
+        egui::ComboBox::from_label("Turmite")
+            .selected_text(target.map_or("none".to_string(), |entity| entity.to_string()))
+            .show_ui(ui, |ui| {
+                ui.selectable_value(&mut target, None, "none");
+                for (entity, _, _) in &query {
+                    ui.selectable_value(&mut target, Some(entity), entity.to_string());
+                }
+            });
+        if target != debugger.target() {
+            command_msg.write(DebuggerCommand::Attach(target));
+        }
+        let Some((_, turmite, program)) = target.and_then(|entity| query.get(entity).ok()) else {
+            return;
+        };
+
+        ui.horizontal(|ui| {
+            if debugger.is_paused() {
+                if ui.button("Continue").clicked() {
+                    command_msg.write(DebuggerCommand::Resume);
+                }
+            } else if ui.button("Pause").clicked() {
+                command_msg.write(DebuggerCommand::Pause);
+            }
+            if ui.button("Step instruction").clicked() {
+                command_msg.write(DebuggerCommand::Step(StepRequest::Instruction));
+            }
+            if ui.button("Step move").clicked() {
+                command_msg.write(DebuggerCommand::Step(StepRequest::Move));
+            }
+        });
+        ui.label(format!(
+            "Position ({}, {}), heading {:?}, state {}",
+            turmite.pos().x,
+            turmite.pos().y,
+            turmite.heading(),
+            turmite.state()
+        ));
+        ui.label(format!("Program {}", program.status()));
+        ui.label(format!(
+            "Instructions: {} ({} since last move)",
+            program.vm().executed(),
+            program.vm().since_move()
+        ));
+        if let Some(reason) = debugger.reason() {
+            ui.label(format!("Paused: {reason}"));
+        }
+
+        ui.separator();
+        let pc = program.vm().pc();
+        ui.columns(2, |columns| {
+            columns[0].strong("Disassembly");
+            egui::ScrollArea::vertical()
+                .id_salt("disassembly")
+                .max_height(320.0)
+                .show(&mut columns[0], |ui| {
+                    for (index, instruction) in program.program().program().instructions().iter().enumerate() {
+                        ui.horizontal(|ui| {
+                            let marker = if debugger.breakpoints().contains(&index) {
+                                "●"
+                            } else {
+                                "○"
+                            };
+                            if ui.small_button(marker).on_hover_text("Toggle breakpoint").clicked() {
+                                command_msg.write(DebuggerCommand::ToggleBreakpoint(index));
+                            }
+                            let mut text = egui::RichText::new(format!("{index:>4}  {instruction}")).monospace();
+                            if index == pc {
+                                text = text.background_color(ui.visuals().selection.bg_fill).strong();
+                            }
+                            ui.label(text);
+                        });
+                    }
+                });
+
+            columns[1].strong("Stack");
+            let stack = program.vm().stack();
+            if stack.is_empty() {
+                columns[1].label("empty");
+            }
+            for value in stack.iter().rev() {
+                columns[1].monospace(value.to_string());
+            }
+
+            let vm = program.vm();
+            columns[1].strong("Calls");
+            if vm.frames().is_empty() {
+                columns[1].label("none");
+            }
+            for return_pc in vm.frames().iter().rev() {
+                columns[1].monospace(format!("returns to {return_pc}"));
+            }
+
+            columns[1].strong("Locals");
+            columns[1].monospace(format_slots(vm.locals()));
+            columns[1].strong("Registers");
+            columns[1].monospace(format_slots(vm.registers()));
+        });
+
+        ui.separator();
+        ui.strong("Break when");
+        let mut removed = None;
+        for (index, condition) in debugger.conditions().enumerate() {
+            ui.horizontal(|ui| {
+                ui.label(condition.to_string());
+                if ui.small_button("Remove").clicked() {
+                    removed = Some(index);
+                }
+            });
+        }
+        if let Some(index) = removed {
+            command_msg.write(DebuggerCommand::RemoveCondition(index));
+        }
+
+        ui.horizontal(|ui| {
+            egui::ComboBox::from_id_salt("condition_kind")
+                .selected_text(match *draft {
+                    BoardCondition::Cell(_) => "cell",
+                    BoardCondition::State(_) => "state",
+                    BoardCondition::Position(_) => "position",
+                })
+                .show_ui(ui, |ui| {
+                    ui.selectable_value(&mut *draft, BoardCondition::Cell(0), "cell");
+                    ui.selectable_value(&mut *draft, BoardCondition::State(0), "state");
+                    ui.selectable_value(&mut *draft, BoardCondition::Position(turmite.pos()), "position");
+                });
+            match &mut *draft {
+                BoardCondition::Cell(value) | BoardCondition::State(value) => {
+                    ui.add(egui::DragValue::new(value));
+                }
+                BoardCondition::Position(pos) => {
+                    ui.add(egui::DragValue::new(&mut pos.x).range(0..=BOARD_SIZE.x - 1));
+                    ui.add(egui::DragValue::new(&mut pos.y).range(0..=BOARD_SIZE.y - 1));
+                }
+            }
+            if ui.button("Add").clicked() {
+                command_msg.write(DebuggerCommand::AddCondition(*draft));
+            }
+        });
+    });
+    Ok(())
+}
+
+pub fn show_programs_panel(mut contexts: EguiContexts, files: Res<ProgramFiles>) -> Result {
+    if files.files().is_empty() {
+        return Ok(());
+    }
+
+    egui::Window::new("Programs").show(contexts.ctx_mut()?, |ui| {
+        for file in files.files() {
+            ui.strong(file.path().display().to_string());
+            match file.error() {
+                Some(err) => ui.colored_label(egui::Color32::RED, format!("{err} (still running the last good version)")),
+                None => ui.label(format!("{} instructions", file.program().program().instructions().len())),
+            };
+        }
+    });
+    Ok(())
+}
+
+// -- Helpers --
+
+/// Slots holding non-zero values, as `index: value` pairs.
+fn format_slots(slots: &[i64]) -> String {
+    let set: Vec<String> = slots
+        .iter()
+        .enumerate()
+        .filter(|(_, value)| **value != 0)
+        .map(|(index, value)| format!("{index}: {value}"))
+        .collect();
+    if set.is_empty() {
+        "all zero".to_string()
+    } else {
+        set.join(", ")
+    }
+}
+
+/// Replace the palette with the one in `file`, keeping the current one if it fails to load.
+fn read_palette_file(file: &PaletteFile, palette: &mut Palette) {
+    let Some(path) = &file.0 else {
+        return;
+    };
+    match Palette::load(path) {
+        Ok(loaded) => {
+            info!("Loaded palette {}", path.display());
+            *palette = loaded;
+        }
+        Err(err) => warn!("Failed to load palette {}: {}", path.display(), err),
+    }
+}
+
+/// Marker size in cells, at least `MARKER_MIN_PIXELS` wide on screen.
+fn marker_scale(ortho: &OrthographicProjection) -> f32 {
+    (MARKER_MIN_PIXELS * ortho.scale).max(1.0)
+}
+
+/// Log-scaled position of `count` along the heat ramp, reaching the end at `scale` visits.
+fn heat_index(count: u32, scale: u32) -> u8 {
+    let t = ((count as f32).ln_1p() / (scale as f32).ln_1p()).clamp(0.0, 1.0);
+    (t * 255.0).round() as u8
+}
+
+/// Black-red-yellow-white ramp across the 256 heat indices.
+fn heat_colour(index: u8) -> u32 {
+    let t = index as f32 / 255.0;
+    let channel = |offset: f32| ((t * 3.0 - offset).clamp(0.0, 1.0) * 255.0) as u8;
+    u32::from_ne_bytes([channel(0.0), channel(1.0), channel(2.0), 255])
+}
@@ -0,0 +1,72 @@
+use std::sync::Arc;
+
+use arc_langton::{
+    components::{Heading, Turmite, TurmiteProgram},
+    debugger::{Debugger, StepRequest},
+    resources::{Memory, ProgramLimits, SimulationStats},
+    settings::BOARD_SIZE,
+    simulation::{Board, TickTurmite},
+};
+use arc_vm::{prelude::*, text};
+use bevy::prelude::*;
+
+/// `MOVE` on the branch that turns right, after the cell has been written.
+const BREAKPOINT: usize = 8;
+
+fn setup(steps_per_tick: usize) -> (Board, [TickTurmite; 1], SimulationStats) {
+    let src = include_str!("data/langton.bc");
+    let executable = text::parse(src).unwrap().verify().unwrap().into_executable(Tier::Compiled);
+    let turmites = [TickTurmite {
+        entity: Entity::from_raw_u32(0).unwrap(),
+        turmite: Turmite::new(BOARD_SIZE / 2, 0, Heading::North),
+        program: Some(TurmiteProgram::new(Arc::new(executable), ProgramLimits::default().limits)),
+    }];
+    let memory = Memory::default();
+    let stats = SimulationStats::from_memory(&memory);
+    let board = Board::new(memory).with_threads(1).with_steps_per_tick(steps_per_tick);
+    (board, turmites, stats)
+}
+
+/// Pausing at a breakpoint partway through a step, then stepping and continuing, ends up where running freely does.
+#[test]
+fn breakpoints_and_stepping_match_running_freely() {
+    let (mut board, mut turmites, mut stats) = setup(7);
+    let mut debugger = Debugger::default();
+    debugger.attach(Some(turmites[0].entity));
+    debugger.toggle_breakpoint(BREAKPOINT);
+
+    let (mut breaks, mut pauses) = (0, 0);
+    for _ in 0..400 {
+        if debugger.is_paused() {
+            pauses += 1;
+            if debugger.reason() == Some(&format!("breakpoint at {BREAKPOINT}")) {
+                // Stopped before the move, with the cell written but the step not yet recorded
+                let program = turmites[0].program.as_ref().unwrap();
+                assert_eq!(program.vm().pc(), BREAKPOINT);
+                assert_eq!(board.memory().read(turmites[0].turmite.pos()), 1);
+                breaks += 1;
+            }
+            match pauses % 3 {
+                0 => debugger.request_step(StepRequest::Instruction),
+                1 => debugger.request_step(StepRequest::Move),
+                _ => debugger.resume(),
+            }
+        }
+        board.tick(&mut turmites, &mut debugger, &mut stats);
+    }
+    // End on a whole step
+    debugger.attach(None);
+    board.tick(&mut turmites, &mut debugger, &mut stats);
+    assert!(breaks > 10);
+
+    let (mut free_board, mut free_turmites, mut free_stats) = setup(1);
+    let mut free_debugger = Debugger::default();
+    while free_stats.steps() < stats.steps() {
+        free_board.tick(&mut free_turmites, &mut free_debugger, &mut free_stats);
+    }
+
+    assert_eq!(free_stats.steps(), stats.steps());
+    assert_eq!(free_stats.colour_counts(), stats.colour_counts());
+    assert_eq!(free_stats.visited_bounds(), stats.visited_bounds());
+    assert_eq!(free_board.state_hash(&free_turmites), board.state_hash(&turmites));
+}
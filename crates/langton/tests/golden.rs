@@ -0,0 +1,262 @@
+//! Well-known turmites run to fixed step counts, checked against published facts about each and the hash of the
+//! board they leave, so a change to stepping that alters any of them shows up here.
+
+use std::sync::Arc;
+
+use arc_langton::{
+    components::{Heading, Turmite, TurmiteProgram},
+    debugger::Debugger,
+    hash::memory_hash,
+    resources::{Memory, ProgramLimits, SimulationStats, Swarm},
+    settings::{BOARD_SIZE, SWARM_STEPS_PER_TICK},
+    simulation::{Board, TickTurmite},
+};
+use arc_vm::{prelude::*, text};
+use bevy::prelude::*;
+
+const START: UVec2 = UVec2::new(BOARD_SIZE.x / 2, BOARD_SIZE.y / 2);
+
+/// A board with turmites on it, stepped one cell at a time.
+struct Run {
+    board: Board,
+    turmites: Vec<TickTurmite>,
+    debugger: Debugger,
+    stats: SimulationStats,
+}
+
+impl Run {
+    /// A single turmite running `src` from the middle of an empty board, facing North.
+    fn new(src: &str) -> Self {
+        Self::with_turmites(src, &[START], 1, 1)
+    }
+
+    fn with_turmites(src: &str, positions: &[UVec2], threads: usize, steps_per_tick: usize) -> Self {
+        let executable = text::parse(src).unwrap().verify().unwrap().into_executable(Tier::Compiled);
+        let executable = Arc::new(executable);
+        let program = || Some(TurmiteProgram::new(executable.clone(), ProgramLimits::default().limits));
+        let board = Board::new(Memory::default())
+            .with_threads(threads)
+            .with_steps_per_tick(steps_per_tick);
+        Self::on_board(board, positions, program)
+    }
+
+    /// A single turmite following the transition table from the middle of an empty board, in state 0.
+    fn table_driven() -> Self {
+        let board = Board::new(Memory::default()).with_threads(1).with_steps_per_tick(1);
+        Self::on_board(board, &[START], || None)
+    }
+
+    /// A swarm on an empty board, with no other turmites.
+    fn swarm(swarm: Swarm) -> Self {
+        Self::on_board(Board::new(Memory::default()).with_swarm(swarm), &[], || None)
+    }
+
+    fn on_board(board: Board, positions: &[UVec2], program: impl Fn() -> Option<TurmiteProgram>) -> Self {
+        let turmites = positions
+            .iter()
+            .enumerate()
+            .map(|(index, &pos)| TickTurmite {
+                entity: Entity::from_raw_u32(index as u32).unwrap(),
+                turmite: Turmite::new(pos, 0, Heading::North),
+                program: program(),
+            })
+            .collect();
+        Self {
+            stats: SimulationStats::from_memory(board.memory()),
+            board,
+            turmites,
+            debugger: Debugger::default(),
+        }
+    }
+
+    fn tick(&mut self) {
+        self.board.tick(&mut self.turmites, &mut self.debugger, &mut self.stats);
+    }
+
+    /// Step until the board has taken `steps` steps in all.
+    fn run_to(&mut self, steps: u64) {
+        while self.stats.steps() < steps {
+            self.tick();
+        }
+        assert_eq!(self.stats.steps(), steps);
+    }
+
+    /// Where the first turmite is, relative to where it started.
+    fn pos(&self) -> IVec2 {
+        self.stats
+            .displacement(self.turmites[0].entity)
+            .unwrap_or_default()
+            .as_ivec2()
+    }
+
+    /// Inclusive bounding box of the painted cells, relative to the start.
+    fn painted_bounds(&self) -> (IVec2, IVec2) {
+        let (mut min, mut max) = (IVec2::MAX, IVec2::MIN);
+        for (index, _) in self
+            .board
+            .memory()
+            .data()
+            .iter()
+            .enumerate()
+            .filter(|(_, value)| **value != 0)
+        {
+            let coord = IVec2::new((index % BOARD_SIZE.x as usize) as i32, (index / BOARD_SIZE.x as usize) as i32);
+            min = min.min(coord);
+            max = max.max(coord);
+        }
+        (min - START.as_ivec2(), max - START.as_ivec2())
+    }
+
+    /// Colour at an offset from the start.
+    fn colour(&self, offset: IVec2) -> u8 {
+        self.board.memory().read(Memory::wrap(START.as_ivec2() + offset))
+    }
+
+    /// Hash of the board and turmites, after checking the board's incremental hash against one from scratch.
+    fn hash(&self) -> u64 {
+        let memory = self.board.memory();
+        assert_eq!(memory.hash(), memory_hash(memory.data()));
+        self.board.state_hash(&self.turmites)
+    }
+}
+
+/// Langton's ant wanders chaotically for about 10,000 steps, then builds a highway, repeating the same 104 steps
+/// two cells further along a diagonal.
+#[test]
+fn langton_builds_a_highway_near_step_10000() {
+    const PERIOD: usize = 104;
+    let mut run = Run::new(include_str!("data/langton.bc"));
+    let mut path = vec![run.pos()];
+    while path.len() <= 11_000 + PERIOD {
+        run.tick();
+        path.push(run.pos());
+        if path.len() == 11_001 {
+            assert_eq!(run.pos(), IVec2::new(-34, -14));
+            assert_eq!(run.painted_bounds(), (IVec2::new(-37, -22), IVec2::new(29, 22)));
+            assert_eq!(run.stats.colour_count(1), 834);
+            assert_eq!(run.hash(), 0x4d33_27bb_b42e_1b40);
+        }
+    }
+
+    // Past the onset, every window of one period moves the ant the same way
+    let cycle = |step: usize| path[step + PERIOD] - path[step];
+    let onset = (0..=11_000).rev().find(|&step| cycle(step) != IVec2::new(-2, -2)).unwrap() + 1;
+    assert_eq!(onset, 9976);
+}
+
+/// The transition table is Langton's ant with the heading kept as the state, so it reaches the same board as the
+/// bytecode ant.
+#[test]
+fn table_driven_ant_builds_the_same_highway() {
+    let mut run = Run::table_driven();
+    run.run_to(11_000);
+    assert_eq!(run.pos(), IVec2::new(-34, -14));
+    assert_eq!(run.painted_bounds(), (IVec2::new(-37, -22), IVec2::new(29, 22)));
+    assert_eq!(run.stats.colour_count(1), 834);
+    assert_eq!(run.hash(), 0xb6f6_32fc_aad0_814e);
+}
+
+/// Ants in a swarm step through the transition table together, each in turn. On its own each would paint 32 cells
+/// in 160 steps, but these start close enough to run into each other's trails.
+#[test]
+fn swarm_ants_cross_paths() {
+    let mut swarm = Swarm::default();
+    for (index, heading) in Heading::ALL.into_iter().enumerate() {
+        let offset = UVec2::new(index as u32 % 2, index as u32 / 2) * 4;
+        swarm.push(START + offset, heading as u8, heading);
+    }
+    let mut run = Run::swarm(swarm);
+    for _ in 0..20 {
+        run.tick();
+    }
+    assert_eq!(run.stats.steps(), 4 * 20 * SWARM_STEPS_PER_TICK as u64);
+    assert_eq!(run.stats.colour_count(1), 98);
+    assert_eq!(run.hash(), 0x0890_23c4_1b20_ce34);
+}
+
+/// LLRR grows symmetrically, coming back to a pattern mirrored about a horizontal line over and over.
+#[test]
+fn llrr_grows_symmetrically() {
+    let mut run = Run::new(include_str!("data/llrr.bc"));
+    let golden = [
+        (
+            1000,
+            (IVec2::new(-4, -4), IVec2::new(3, 3)),
+            [0, 32, 24],
+            0x728c_f189_76eb_d1ab,
+        ),
+        (
+            2500,
+            (IVec2::new(-7, -7), IVec2::new(4, 6)),
+            [32, 58, 0],
+            0x3784_fa6e_c848_94c5,
+        ),
+        (
+            4000,
+            (IVec2::new(-8, -9), IVec2::new(4, 8)),
+            [72, 60, 16],
+            0xb830_4ec5_5b20_774a,
+        ),
+        (
+            10_012,
+            (IVec2::new(-12, -12), IVec2::new(7, 11)),
+            [64, 158, 56],
+            0xa3f3_4522_43b7_f3fb,
+        ),
+    ];
+    for (steps, bounds, counts, hash) in golden {
+        run.run_to(steps);
+        assert_eq!(run.pos(), IVec2::ZERO, "after {steps} steps");
+        assert_eq!(run.painted_bounds(), bounds, "after {steps} steps");
+        assert_eq!(
+            [1, 2, 3].map(|value| run.stats.colour_count(value)),
+            counts,
+            "after {steps} steps"
+        );
+
+        let (min, max) = bounds;
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let mirrored = IVec2::new(x, min.y + max.y - y);
+                assert_eq!(run.colour(IVec2::new(x, y)), run.colour(mirrored), "after {steps} steps");
+            }
+        }
+        assert_eq!(run.hash(), hash, "after {steps} steps");
+    }
+}
+
+/// The square builder fills a solid square, completing one `2n` cells across after `8n² - 2n - 2` steps.
+#[test]
+fn square_builder_fills_solid_squares() {
+    let mut run = Run::new(include_str!("data/square.bc"));
+    let golden = [
+        (1, 0xea6b_723a_e6e3_356c),
+        (5, 0x2057_1141_a144_560f),
+        (20, 0x4256_fba3_4dbb_c7b6),
+        (50, 0xc5f5_4cd9_ec7f_e09d),
+    ];
+    for (n, hash) in golden {
+        let steps = 8 * n * n - 2 * n - 2;
+        let side = 2 * n as i32;
+        run.run_to(steps);
+        let (min, max) = run.painted_bounds();
+        assert_eq!(max - min + 1, IVec2::splat(side), "after {steps} steps");
+        assert_eq!(run.stats.colour_count(1), (side * side) as u64, "after {steps} steps");
+        assert_eq!(run.hash(), hash, "after {steps} steps");
+    }
+}
+
+/// Ants close enough to cross paths end up the same however many threads step them.
+#[test]
+fn threaded_ants_match_stepping_in_order() {
+    let positions: Vec<_> = (0..16).map(|index| START + UVec2::new(index % 4, index / 4) * 40).collect();
+    let hashes = [1, 4].map(|threads| {
+        let mut run = Run::with_turmites(include_str!("data/langton.bc"), &positions, threads, 1000);
+        for _ in 0..20 {
+            run.tick();
+        }
+        assert_eq!(run.stats.steps(), 16 * 20 * 1000);
+        run.hash()
+    });
+    assert_eq!(hashes, [0xf0e5_c3e7_9041_e313; 2]);
+}
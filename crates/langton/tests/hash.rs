@@ -0,0 +1,108 @@
+use std::sync::Arc;
+
+use arc_langton::{
+    components::{Heading, Turmite, TurmiteProgram},
+    debugger::Debugger,
+    hash::memory_hash,
+    resources::{Memory, ProgramLimits, Revisit, SimulationStats, StateHistory},
+    settings::BOARD_SIZE,
+    simulation::{Board, TickTurmite},
+};
+use arc_vm::{prelude::*, text};
+use bevy::prelude::*;
+
+#[test]
+fn incremental_hash_matches_hashing_from_scratch() {
+    let mut memory = Memory::default();
+    assert_eq!(memory.hash(), 0);
+
+    let writes = [
+        ((3, 4), 1),
+        ((5, 6), 2),
+        ((3, 4), 3),
+        ((0, 0), 1),
+        ((5, 6), 2),
+        ((BOARD_SIZE.x - 1, 7), 255),
+    ];
+    for ((x, y), value) in writes {
+        memory.write(UVec2::new(x, y), value);
+        assert_eq!(
+            memory.hash(),
+            memory_hash(memory.data()),
+            "after writing {value} to ({x}, {y})"
+        );
+    }
+
+    // Clearing every cell again brings the hash back to that of an empty board
+    for ((x, y), _) in writes {
+        memory.write(UVec2::new(x, y), 0);
+    }
+    assert_eq!(memory.hash(), 0);
+    assert_eq!(memory_hash(memory.data()), 0);
+}
+
+#[test]
+fn records_only_the_first_revisit() {
+    let mut history = StateHistory::default();
+    assert_eq!(history.record(10), None);
+    assert_eq!(history.record(20), None);
+    assert_eq!(history.record(30), None);
+    assert_eq!(history.record(20), Some(Revisit { first: 1, again: 3 }));
+    assert_eq!(history.record(10), None);
+    assert_eq!(history.revisit(), Some(Revisit { first: 1, again: 3 }));
+    assert_eq!(history.latest(), Some(10));
+}
+
+#[test]
+fn forgets_hashes_past_its_capacity() {
+    let mut history = StateHistory::with_capacity(3);
+    for hash in [1, 2, 3, 4] {
+        assert_eq!(history.record(hash), None);
+    }
+
+    // 1 has been forgotten, so counts as new again and pushes out 2
+    assert_eq!(history.record(1), None);
+    assert_eq!(history.record(2), None);
+    assert_eq!(history.record(4), Some(Revisit { first: 3, again: 6 }));
+
+    history.clear();
+    assert_eq!(history.revisit(), None);
+    assert_eq!(history.latest(), None);
+    assert_eq!(history.record(4), None);
+}
+
+/// A turmite walking straight up an empty board wraps around, coming back to where it was one board height later.
+#[test]
+fn detects_a_simulation_coming_back_around() {
+    let executable = text::parse("MOVE\nJUMP 0\nHALT\n")
+        .unwrap()
+        .verify()
+        .unwrap()
+        .into_executable(Tier::Compiled);
+    let mut turmites = [TickTurmite {
+        entity: Entity::from_raw_u32(0).unwrap(),
+        turmite: Turmite::new(BOARD_SIZE / 2, 0, Heading::North),
+        program: Some(TurmiteProgram::new(Arc::new(executable), ProgramLimits::default().limits)),
+    }];
+    let memory = Memory::default();
+    let mut stats = SimulationStats::from_memory(&memory);
+    let mut board = Board::new(memory).with_threads(1).with_steps_per_tick(1);
+    let mut debugger = Debugger::default();
+    let mut history = StateHistory::default();
+
+    let mut revisit = None;
+    for _ in 0..2 * BOARD_SIZE.y {
+        board.tick(&mut turmites, &mut debugger, &mut stats);
+        if let Some(found) = history.record(board.state_hash(&turmites)) {
+            assert!(revisit.is_none());
+            revisit = Some(found);
+        }
+    }
+    assert_eq!(
+        revisit,
+        Some(Revisit {
+            first: 0,
+            again: BOARD_SIZE.y as u64
+        })
+    );
+}
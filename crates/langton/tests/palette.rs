@@ -0,0 +1,74 @@
+use std::{env, fs, process};
+
+use arc_langton::palette::{Palette, PaletteError};
+
+fn rgba(colour: [u8; 4]) -> u32 {
+    u32::from_ne_bytes(colour)
+}
+
+#[test]
+fn parses_a_palette_file() {
+    let src = "; two colours and a translucent one\n\
+               #ffffff\n\
+               \n\
+               000000   ; black\n\
+               #12345680\n";
+    let palette = Palette::parse(src).unwrap();
+    assert_eq!(palette.scheme(), None);
+    assert_eq!(palette.colour(0), rgba([0xff, 0xff, 0xff, 0xff]));
+    assert_eq!(palette.colour(1), rgba([0x00, 0x00, 0x00, 0xff]));
+    assert_eq!(palette.colour(2), rgba([0x12, 0x34, 0x56, 0x80]));
+    // Values past the end of the file cycle back through it
+    assert_eq!(palette.colour(3), palette.colour(0));
+    assert_eq!(palette.colour(255), palette.colour(0));
+}
+
+#[test]
+fn loads_a_palette_file() {
+    let path = env::temp_dir().join(format!("arc-langton-palette-{}.txt", process::id()));
+    fs::write(&path, "#ff0000\n#00ff00\n").unwrap();
+    let palette = Palette::load(&path);
+    fs::remove_file(&path).unwrap();
+    assert_eq!(palette.unwrap().colour(1), rgba([0x00, 0xff, 0x00, 0xff]));
+}
+
+#[test]
+fn reports_a_missing_file() {
+    let path = env::temp_dir().join("arc-langton-palette-missing.txt");
+    assert!(matches!(Palette::load(path), Err(PaletteError::Io(_))));
+}
+
+#[test]
+fn reports_invalid_colours() {
+    for (src, text) in [
+        ("#ffffff\n#fffff\n", "#fffff"),
+        ("#ffffff\n#gggggg ; not hex\n", "#gggggg"),
+        ("#ffffff\nffffff0\n", "ffffff0"),
+        ("#ffffff\n#ffé\u{301}ff\n", "#ffé\u{301}ff"),
+    ] {
+        match Palette::parse(src) {
+            Err(PaletteError::InvalidColour { line, text: found }) => {
+                assert_eq!(line, 2, "{src:?}");
+                assert_eq!(found, text);
+            }
+            other => panic!("{src:?} gave {:?}", other.err()),
+        }
+    }
+}
+
+#[test]
+fn reports_too_many_colours() {
+    let src = "; header\n".to_string() + &"#000000\n".repeat(257);
+    assert!(matches!(
+        Palette::parse(&src),
+        Err(PaletteError::TooManyColours { line: 258 })
+    ));
+    assert!(Palette::parse(&"#000000\n".repeat(256)).is_ok());
+}
+
+#[test]
+fn reports_an_empty_palette() {
+    for src in ["", "\n\n", "; only a comment\n   ; and another\n"] {
+        assert!(matches!(Palette::parse(src), Err(PaletteError::Empty)), "{src:?}");
+    }
+}
@@ -0,0 +1,107 @@
+use std::{
+    env, fs,
+    path::{Path, PathBuf},
+    process,
+    sync::Arc,
+    time::{Duration, SystemTime},
+};
+
+use arc_langton::programs::{ProgramError, ProgramFiles};
+use arc_vm::prelude::*;
+
+/// Scratch file for one test, removed when dropped.
+struct TempFile(PathBuf);
+
+impl TempFile {
+    fn new(name: &str) -> Self {
+        Self(env::temp_dir().join(format!("arc-langton-{name}-{}.bc", process::id())))
+    }
+
+    /// Overwrite the file, stamping it `generation` seconds on, so every edit has a distinct modified time.
+    fn write(&self, src: &str, generation: u64) {
+        fs::write(&self.0, src).unwrap();
+        let file = fs::File::options().write(true).open(&self.0).unwrap();
+        file.set_modified(SystemTime::UNIX_EPOCH + Duration::from_secs(1_000_000 + generation))
+            .unwrap();
+    }
+
+    fn path(&self) -> &Path {
+        &self.0
+    }
+}
+
+impl Drop for TempFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+fn instructions(program: &Executable) -> usize {
+    program.program().instructions().len()
+}
+
+#[test]
+fn loads_each_file_once() {
+    let file = TempFile::new("once");
+    file.write("MOVE\nJUMP 0\nHALT\n", 0);
+    let mut files = ProgramFiles::default();
+    let first = files.load(file.path(), Tier::Compiled).unwrap();
+    let second = files.load(file.path(), Tier::Compiled).unwrap();
+    assert!(Arc::ptr_eq(&first, &second));
+    assert_eq!(files.files().len(), 1);
+
+    let missing = TempFile::new("missing");
+    assert!(matches!(files.load(missing.path(), Tier::Compiled), Err(ProgramError::Io(_))));
+    assert_eq!(files.files().len(), 1);
+}
+
+#[test]
+fn editing_a_file_swaps_its_program() {
+    let file = TempFile::new("edit");
+    file.write("MOVE\nJUMP 0\nHALT\n", 0);
+    let mut files = ProgramFiles::default();
+    let first = files.load(file.path(), Tier::Compiled).unwrap();
+
+    // Unchanged files aren't reloaded
+    files.poll();
+    assert!(files.take_reloaded().is_empty());
+
+    file.write("PUSH 1\nTURN\nMOVE\nJUMP 0\nHALT\n", 1);
+    files.poll();
+    let reloaded = files.take_reloaded();
+    assert_eq!(reloaded.len(), 1);
+    let (old, new) = &reloaded[0];
+    assert!(Arc::ptr_eq(old, &first));
+    assert_eq!(instructions(new), 5);
+    assert!(Arc::ptr_eq(files.files()[0].program(), new));
+    assert!(files.files()[0].error().is_none());
+    assert!(files.take_reloaded().is_empty());
+}
+
+#[test]
+fn broken_edits_keep_the_old_program() {
+    let file = TempFile::new("broken");
+    file.write("MOVE\nJUMP 0\nHALT\n", 0);
+    let mut files = ProgramFiles::default();
+    let first = files.load(file.path(), Tier::Compiled).unwrap();
+
+    file.write("MOVE\nFLY\nHALT\n", 1);
+    files.poll();
+    assert!(files.take_reloaded().is_empty());
+    assert!(Arc::ptr_eq(files.files()[0].program(), &first));
+    assert!(matches!(files.files()[0].error(), Some(ProgramError::Parse(_))));
+
+    // Underflows the stack, so parses but doesn't verify
+    file.write("ADD\nMOVE\nHALT\n", 2);
+    files.poll();
+    assert!(files.take_reloaded().is_empty());
+    assert!(Arc::ptr_eq(files.files()[0].program(), &first));
+    assert!(matches!(files.files()[0].error(), Some(ProgramError::Verify(_))));
+
+    // Fixing the file clears the error
+    file.write("PUSH 1\nTURN\nMOVE\nJUMP 0\nHALT\n", 3);
+    files.poll();
+    assert_eq!(files.take_reloaded().len(), 1);
+    assert_eq!(instructions(files.files()[0].program()), 5);
+    assert!(files.files()[0].error().is_none());
+}
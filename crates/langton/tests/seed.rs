@@ -0,0 +1,90 @@
+use arc_langton::{
+    components::{Heading, Turmite},
+    debugger::Debugger,
+    resources::{BoardSeed, Memory, SeedRegion, SimulationStats, Swarm},
+    settings::{BOARD_SIZE, SWARM_STEPS_PER_TICK},
+    simulation::{Board, TickTurmite},
+};
+use arc_random::resources::SeededRng;
+use bevy::prelude::*;
+
+fn seeded(board_seed: &BoardSeed, seed: u64) -> Memory {
+    let mut memory = Memory::default();
+    board_seed.apply(&mut memory, SeededRng::new(seed).rng());
+    memory
+}
+
+fn modes() -> [BoardSeed; 4] {
+    [
+        BoardSeed::uniform(0.3),
+        BoardSeed::square(256, 0.5),
+        BoardSeed::disc(100, 0.5),
+        BoardSeed::weighted(SeedRegion::Square { size: 128 }, vec![1.0, 2.0, 3.0]),
+    ]
+}
+
+#[test]
+fn same_seed_gives_the_same_board() {
+    for board_seed in modes() {
+        let first = seeded(&board_seed, 42);
+        let second = seeded(&board_seed, 42);
+        assert!(first.data() == second.data());
+        assert_eq!(first.hash(), second.hash());
+        assert!(first.data().iter().any(|&value| value != 0));
+    }
+}
+
+#[test]
+fn different_seeds_give_different_boards() {
+    for board_seed in modes() {
+        assert!(seeded(&board_seed, 1).data() != seeded(&board_seed, 2).data());
+    }
+}
+
+#[test]
+fn seeding_stays_inside_its_region() {
+    for board_seed in modes() {
+        let memory = seeded(&board_seed, 7);
+        let (min, max) = board_seed.region.bounds();
+        for (index, &value) in memory.data().iter().enumerate().filter(|(_, value)| **value != 0) {
+            let coord = UVec2::new(index as u32 % BOARD_SIZE.x, index as u32 / BOARD_SIZE.x);
+            assert!(coord.cmpge(min).all() && coord.cmplt(max).all());
+            assert!(board_seed.region.contains(coord));
+            assert!(value <= 2);
+        }
+    }
+}
+
+/// The transition table only tells 0 from any other colour, so seeded colours past 1 are stepped on like 1.
+#[test]
+fn table_turmites_step_over_any_seeded_colour() {
+    let board_seed = BoardSeed::weighted(SeedRegion::Square { size: 32 }, vec![1.0, 1.0, 1.0, 1.0]);
+    let mut memory = seeded(&board_seed, 9);
+    let centre = BOARD_SIZE / 2;
+    memory.write(centre, 2);
+
+    let mut swarm = Swarm::default();
+    for index in 0..16 {
+        swarm.push(centre + UVec2::new(index % 4, index / 4) * 3, 0, Heading::North);
+    }
+    let mut turmites = [TickTurmite {
+        entity: Entity::from_raw_u32(0).unwrap(),
+        turmite: Turmite::new(centre, 0, Heading::North),
+        program: None,
+    }];
+    let mut stats = SimulationStats::from_memory(&memory);
+    let mut board = Board::new(memory).with_threads(1).with_steps_per_tick(1).with_swarm(swarm);
+    let mut debugger = Debugger::default();
+
+    // Colour 2 turns the turmite left and is cleared, as 1 would be
+    board.tick(&mut turmites, &mut debugger, &mut stats);
+    assert_eq!(turmites[0].turmite.heading(), Heading::West);
+    assert_eq!(turmites[0].turmite.pos(), centre - UVec2::X);
+    assert_eq!(board.memory().read(centre), 0);
+
+    for _ in 0..200 {
+        board.tick(&mut turmites, &mut debugger, &mut stats);
+    }
+    assert_eq!(stats.steps(), 201 * (1 + 16 * SWARM_STEPS_PER_TICK as u64));
+    assert_eq!(stats.colour_counts().iter().sum::<u64>(), BOARD_SIZE.element_product() as u64);
+}
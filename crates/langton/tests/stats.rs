@@ -0,0 +1,147 @@
+use std::sync::Arc;
+
+use arc_langton::{
+    components::{Heading, ProgramStatus, Turmite, TurmiteProgram},
+    debugger::{Debugger, StepRequest},
+    resources::{Memory, ProgramLimits, SimulationStats},
+    settings::BOARD_SIZE,
+    simulation::{Board, TickTurmite},
+};
+use arc_vm::{prelude::*, text};
+use bevy::{math::I64Vec2, prelude::*};
+
+fn entity(index: u32) -> Entity {
+    Entity::from_raw_u32(index).unwrap()
+}
+
+/// Run `src` from the middle of an empty board until it has taken `steps` steps or stopped, one cell per tick or,
+/// with `single_step`, one instruction per tick under the debugger.
+fn run(src: &str, steps: u64, single_step: bool) -> (Board, SimulationStats) {
+    let executable = text::parse(src).unwrap().verify().unwrap().into_executable(Tier::Compiled);
+    let mut turmites = [TickTurmite {
+        entity: entity(0),
+        turmite: Turmite::new(BOARD_SIZE / 2, 0, Heading::North),
+        program: Some(TurmiteProgram::new(Arc::new(executable), ProgramLimits::default().limits)),
+    }];
+    let memory = Memory::default();
+    let mut stats = SimulationStats::from_memory(&memory);
+    let mut board = Board::new(memory).with_threads(1).with_steps_per_tick(1);
+    let mut debugger = Debugger::default();
+    if single_step {
+        debugger.attach(Some(entity(0)));
+    }
+
+    let running = |turmites: &[TickTurmite]| *turmites[0].program.as_ref().unwrap().status() == ProgramStatus::Running;
+    while stats.steps() < steps && running(&turmites) {
+        if single_step {
+            debugger.request_step(StepRequest::Instruction);
+        }
+        board.tick(&mut turmites, &mut debugger, &mut stats);
+    }
+    (board, stats)
+}
+
+#[test]
+fn counts_colours_from_the_board_and_each_step() {
+    let mut memory = Memory::default();
+    memory.write(UVec2::new(3, 4), 1);
+    memory.write(UVec2::new(5, 6), 2);
+    let mut stats = SimulationStats::from_memory(&memory);
+    let cells = BOARD_SIZE.element_product() as u64;
+    assert_eq!(stats.colour_count(0), cells - 2);
+    assert_eq!(stats.colour_count(1), 1);
+    assert_eq!(stats.colour_count(2), 1);
+
+    stats.record_step(entity(0), UVec2::new(0, 0), IVec2::X, 0, 1);
+    stats.record_step(entity(0), UVec2::new(1, 0), IVec2::X, 0, 2);
+    stats.record_step(entity(0), UVec2::new(3, 4), IVec2::X, 1, 0);
+    assert_eq!(stats.colour_count(0), cells - 3);
+    assert_eq!(stats.colour_count(1), 1);
+    assert_eq!(stats.colour_count(2), 2);
+    assert_eq!(stats.colour_counts().iter().sum::<u64>(), cells);
+    assert_eq!(stats.steps(), 3);
+}
+
+#[test]
+fn tracks_displacement_per_turmite() {
+    let mut stats = SimulationStats::from_memory(&Memory::default());
+    assert_eq!(stats.displacement(entity(0)), None);
+
+    for delta in [IVec2::X, IVec2::X, IVec2::Y, IVec2::NEG_X] {
+        stats.record_step(entity(0), UVec2::ZERO, delta, 0, 0);
+    }
+    stats.record_step(entity(1), UVec2::ZERO, IVec2::NEG_Y, 0, 0);
+    // Swarm turmites and stopped programs add no displacement
+    stats.record_swarm_step(UVec2::ZERO, 0, 0);
+    stats.record_step(entity(1), UVec2::ZERO, IVec2::ZERO, 0, 0);
+
+    assert_eq!(stats.displacement(entity(0)), Some(I64Vec2::new(1, 1)));
+    assert_eq!(stats.displacement(entity(1)), Some(I64Vec2::new(0, -1)));
+    assert_eq!(stats.displacements().count(), 2);
+    assert_eq!(stats.steps(), 7);
+}
+
+#[test]
+fn bounds_every_visited_cell() {
+    let mut stats = SimulationStats::from_memory(&Memory::default());
+    assert_eq!(stats.visited_bounds(), None);
+
+    stats.record_step(entity(0), UVec2::new(10, 20), IVec2::X, 0, 0);
+    assert_eq!(stats.visited_bounds(), Some((UVec2::new(10, 20), UVec2::new(10, 20))));
+    stats.record_step(entity(0), UVec2::new(12, 15), IVec2::X, 0, 0);
+    stats.record_swarm_step(UVec2::new(8, 30), 0, 0);
+    assert_eq!(stats.visited_bounds(), Some((UVec2::new(8, 15), UVec2::new(12, 30))));
+}
+
+/// Crossing the edge of the board keeps the displacement unwrapped, while the visited cells lie on both edges.
+#[test]
+fn wraps_at_the_board_edge() {
+    let src = "PUSH 1\nTURN\nloop: MOVE\nJUMP loop\n";
+    let executable = text::parse(src).unwrap().verify().unwrap().into_executable(Tier::Compiled);
+    let start = UVec2::new(BOARD_SIZE.x - 2, 7);
+    let mut turmites = [TickTurmite {
+        entity: entity(0),
+        turmite: Turmite::new(start, 0, Heading::North),
+        program: Some(TurmiteProgram::new(Arc::new(executable), ProgramLimits::default().limits)),
+    }];
+    let memory = Memory::default();
+    let mut stats = SimulationStats::from_memory(&memory);
+    let mut board = Board::new(memory).with_threads(1).with_steps_per_tick(4);
+    board.tick(&mut turmites, &mut Debugger::default(), &mut stats);
+
+    assert_eq!(turmites[0].turmite.pos(), UVec2::new(2, 7));
+    assert_eq!(stats.displacement(entity(0)), Some(I64Vec2::new(4, 0)));
+    assert_eq!(
+        stats.visited_bounds(),
+        Some((UVec2::new(0, 7), UVec2::new(BOARD_SIZE.x - 1, 7)))
+    );
+    assert_eq!(stats.steps(), 4);
+}
+
+/// Pausing between an instruction that writes and the `MOVE` that ends the step still records the step once.
+#[test]
+fn single_stepping_records_the_same_stats() {
+    let programs = [
+        (include_str!("data/langton.bc"), 300),
+        // Writes before halting, which counts as a step without moving
+        ("PUSH 1\nTURN\nPUSH 1\nWRITE\nMOVE\nPUSH 2\nWRITE\nHALT\n", u64::MAX),
+    ];
+    for (src, steps) in programs {
+        let (free_board, free) = run(src, steps, false);
+        let (stepped_board, stepped) = run(src, steps, true);
+
+        assert_eq!(stepped.steps(), free.steps());
+        assert_eq!(stepped.colour_counts(), free.colour_counts());
+        assert_eq!(stepped.visited_bounds(), free.visited_bounds());
+        assert_eq!(stepped.displacement(entity(0)), free.displacement(entity(0)));
+        assert_eq!(stepped_board.memory().hash(), free_board.memory().hash());
+
+        let (min, max) = free.visited_bounds().unwrap();
+        for y in min.y..=max.y {
+            for x in min.x..=max.x {
+                let coord = UVec2::new(x, y);
+                assert_eq!(stepped_board.visits().read(coord), free_board.visits().read(coord));
+            }
+        }
+    }
+}
@@ -0,0 +1,117 @@
+use arc_langton::{
+    components::{Heading, MARKER, Turmite, coord_to_world_pos},
+    debugger::Debugger,
+    resources::{Memory, SeedRegion, SimulationStats, Swarm, SwarmSpawns},
+    settings::{BOARD_SIZE, SWARM_STEPS_PER_TICK},
+    simulation::{Board, TickTurmite},
+};
+use arc_random::resources::SeededRng;
+use bevy::prelude::*;
+
+fn spawns(count: usize, region: SeedRegion) -> SwarmSpawns {
+    SwarmSpawns { count, region }
+}
+
+#[test]
+fn empty_regions_spawn_no_swarm() {
+    let swarm = spawns(10, SeedRegion::Square { size: 0 }).apply(SeededRng::new(1).rng());
+    assert!(swarm.is_empty());
+
+    // A disc of radius 0 still holds its centre
+    let swarm = spawns(10, SeedRegion::Disc { radius: 0 }).apply(SeededRng::new(1).rng());
+    assert_eq!(swarm.len(), 10);
+    assert!(swarm.positions.iter().all(|&pos| pos == BOARD_SIZE / 2));
+}
+
+/// Swarm turmites follow the same transition table as entity turmites without a program, in the same order.
+#[test]
+fn swarm_steps_like_table_driven_turmites() {
+    let swarm = spawns(64, SeedRegion::Square { size: 24 }).apply(SeededRng::new(3).rng());
+    let mut turmites: Vec<_> = (0..swarm.len())
+        .map(|index| TickTurmite {
+            entity: Entity::from_raw_u32(index as u32).unwrap(),
+            turmite: Turmite::new(swarm.positions[index], swarm.states[index], swarm.headings[index]),
+            program: None,
+        })
+        .collect();
+
+    let memory = Memory::default();
+    let mut swarm_stats = SimulationStats::from_memory(&memory);
+    let mut turmite_stats = SimulationStats::from_memory(&memory);
+    let mut swarm_board = Board::new(memory.clone()).with_swarm(swarm);
+    let mut turmite_board = Board::new(memory).with_threads(1).with_steps_per_tick(SWARM_STEPS_PER_TICK);
+    let mut debugger = Debugger::default();
+    for _ in 0..50 {
+        swarm_board.tick(&mut [], &mut debugger, &mut swarm_stats);
+        turmite_board.tick(&mut turmites, &mut debugger, &mut turmite_stats);
+    }
+
+    let swarm = swarm_board.swarm();
+    for (index, tick) in turmites.iter().enumerate() {
+        assert_eq!(swarm.positions[index], tick.turmite.pos(), "turmite {index}");
+        assert_eq!(swarm.states[index], tick.turmite.state(), "turmite {index}");
+        assert_eq!(swarm.headings[index], tick.turmite.heading(), "turmite {index}");
+    }
+    assert_eq!(swarm_board.memory().hash(), turmite_board.memory().hash());
+    assert_eq!(swarm_stats.steps(), 64 * 50 * SWARM_STEPS_PER_TICK as u64);
+    assert_eq!(swarm_stats.colour_counts(), turmite_stats.colour_counts());
+    assert_eq!(swarm_stats.visited_bounds(), turmite_stats.visited_bounds());
+}
+
+#[test]
+fn paused_swarms_stay_put() {
+    let swarm = spawns(8, SeedRegion::Full).apply(SeededRng::new(5).rng());
+    let memory = Memory::default();
+    let mut stats = SimulationStats::from_memory(&memory);
+    let mut board = Board::new(memory).with_swarm(swarm.clone());
+    let mut debugger = Debugger::default();
+    debugger.pause();
+
+    let cells = board.tick(&mut [], &mut debugger, &mut stats);
+    assert!(cells.is_empty());
+    assert_eq!(board.swarm().positions, swarm.positions);
+    assert_eq!(stats.steps(), 0);
+}
+
+#[test]
+fn marker_vertices_follow_each_turmite() {
+    let mut swarm = Swarm::default();
+    swarm.push(UVec2::new(10, 20), 0, Heading::North);
+    swarm.push(UVec2::new(30, 5), 0, Heading::East);
+
+    let vertices = swarm.marker_vertices(2.0);
+    assert_eq!(vertices.len(), 2 * MARKER.len());
+    for (index, triangle) in vertices.chunks(MARKER.len()).enumerate() {
+        let centre = coord_to_world_pos(swarm.positions[index]);
+        let rotation = swarm.headings[index].rotation();
+        for (vertex, corner) in triangle.iter().zip(MARKER) {
+            let expected = centre + rotation * corner.extend(0.0) * 2.0;
+            assert!(
+                Vec3::from_array(*vertex).abs_diff_eq(expected, 1e-3),
+                "{vertex:?} != {expected}"
+            );
+        }
+    }
+
+    // The North-facing marker's tip is straight above its cell, and the East-facing one's straight to the right
+    let tip = |index: usize| Vec3::from_array(vertices[index * MARKER.len()]) - coord_to_world_pos(swarm.positions[index]);
+    assert!(tip(0).abs_diff_eq(Vec3::new(0.0, 1.0, 0.0), 1e-3));
+    assert!(tip(1).abs_diff_eq(Vec3::new(1.0, 0.0, 0.0), 1e-3));
+
+    // Stepping moves the markers with the turmites
+    let memory = Memory::default();
+    let mut stats = SimulationStats::from_memory(&memory);
+    let mut board = Board::new(memory).with_swarm(swarm.clone());
+    board.tick(&mut [], &mut Debugger::default(), &mut stats);
+    let moved = board.swarm().marker_vertices(2.0);
+    assert_ne!(moved, vertices);
+    let expected = coord_to_world_pos(board.swarm().positions[0]);
+    let centroid = moved[..MARKER.len()]
+        .iter()
+        .map(|&vertex| Vec3::from_array(vertex))
+        .sum::<Vec3>()
+        / 3.0;
+    let marker_centroid = MARKER.iter().sum::<Vec2>() / 3.0 * 2.0;
+    let offset = board.swarm().headings[0].rotation() * marker_centroid.extend(0.0);
+    assert!(centroid.abs_diff_eq(expected + offset, 1e-3));
+}
@@ -0,0 +1,51 @@
+use std::sync::Arc;
+
+use arc_langton::{
+    components::{Heading, Turmite, TurmiteProgram},
+    debugger::Debugger,
+    resources::{Memory, ProgramLimits, SimulationStats},
+    settings::BOARD_SIZE,
+    simulation::{Board, TickTurmite},
+};
+use arc_vm::{prelude::*, text};
+use bevy::prelude::*;
+
+/// Program and table turmites share the board, so the table has to handle whatever colours programs write.
+#[test]
+fn table_turmites_step_over_colours_programs_write() {
+    let executable = text::parse("PUSH 2\nWRITE\nPUSH 1\nTURN\nMOVE\nJUMP 0\nHALT\n")
+        .unwrap()
+        .verify()
+        .unwrap()
+        .into_executable(Tier::Compiled);
+    let centre = BOARD_SIZE / 2;
+    let mut turmites = [
+        TickTurmite {
+            entity: Entity::from_raw_u32(0).unwrap(),
+            turmite: Turmite::new(centre, 0, Heading::North),
+            program: Some(TurmiteProgram::new(Arc::new(executable), ProgramLimits::default().limits)),
+        },
+        TickTurmite {
+            entity: Entity::from_raw_u32(1).unwrap(),
+            turmite: Turmite::new(centre, 0, Heading::North),
+            program: None,
+        },
+    ];
+    let memory = Memory::default();
+    let mut stats = SimulationStats::from_memory(&memory);
+    let mut board = Board::new(memory).with_threads(1).with_steps_per_tick(1);
+    let mut debugger = Debugger::default();
+
+    // The program paints its cell 2 and steps off it, then the table turmite reads the 2 like a 1
+    board.tick(&mut turmites, &mut debugger, &mut stats);
+    assert_eq!(turmites[1].turmite.heading(), Heading::West);
+    assert_eq!(turmites[1].turmite.pos(), centre - UVec2::X);
+    assert_eq!(board.memory().read(centre), 0);
+
+    // Keep both going while the program circles a 2x2 square of 2s
+    for _ in 0..500 {
+        board.tick(&mut turmites, &mut debugger, &mut stats);
+    }
+    assert_eq!(stats.steps(), 2 * 501);
+    assert!(stats.colour_count(2) > 0);
+}
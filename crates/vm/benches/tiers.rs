@@ -0,0 +1,85 @@
+//! Compare the interpreter with compiled closures on the same programs.
+//!
+//! Run with `cargo bench -p arc_vm`.
+
+#[path = "../tests/common/mod.rs"]
+mod common;
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use arc_vm::{prelude::*, text};
+use common::Board;
+
+/// Timed runs of each tier, keeping the fastest.
+const RUNS: usize = 5;
+
+// Sum `3i + 1` for `i` from 200 down to 1, counting in the state, then move once
+const ARITHMETIC: &str = "\
+PUSH 200
+SET_STATE
+PUSH 0
+STATE
+PUSH 0
+GT
+JUMP_IF_NOT 18
+STATE
+PUSH 3
+MUL
+PUSH 1
+ADD
+ADD
+STATE
+PUSH 1
+SUB
+SET_STATE
+JUMP 3
+POP
+MOVE
+JUMP 0
+";
+
+fn main() {
+    bench("langton", include_str!("../tests/data/langton.bc"), 1_000_000);
+    bench("arithmetic", ARITHMETIC, 10_000);
+}
+
+fn bench(name: &str, src: &str, moves: usize) {
+    let program = text::parse(src).unwrap();
+    let verified = program.clone().verify().unwrap_or_else(|err| panic!("{name}: {err}"));
+    let interpreted = verified.clone().into_executable(Tier::Interpreted);
+    let compiled = verified.into_executable(Tier::Compiled);
+
+    let checked = time(moves, |vm, board| vm.resume(&program, board));
+    let unchecked = time(moves, |vm, board| vm.resume_executable(&interpreted, board, u64::MAX));
+    let closures = time(moves, |vm, board| vm.resume_executable(&compiled, board, u64::MAX));
+
+    println!("{name}:");
+    report("checked interpreter", checked, checked, moves);
+    report("verified interpreter", unchecked, checked, moves);
+    report("compiled threaded code", closures, checked, moves);
+}
+
+fn time(moves: usize, mut resume: impl FnMut(&mut Vm, &mut Board) -> Result<Exit, VmError>) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let mut vm = Vm::new();
+            let mut board = Board::new();
+            let start = Instant::now();
+            for _ in 0..moves {
+                black_box(resume(&mut vm, &mut board).unwrap());
+            }
+            black_box(&board.cells);
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn report(label: &str, elapsed: Duration, baseline: Duration, moves: usize) {
+    let per_move = elapsed.as_secs_f64() * 1e9 / moves as f64;
+    let speedup = baseline.as_secs_f64() / elapsed.as_secs_f64();
+    println!("  {label:<24} {per_move:>9.1} ns/move  {speedup:>5.2}x");
+}
@@ -0,0 +1,216 @@
+use std::{error::Error, fmt};
+
+use super::{
+    direction::Direction,
+    instruction::{Instruction, Opcode, Operand, OperandKind},
+    program::Program,
+    text,
+};
+
+pub const MAGIC: [u8; 4] = *b"LTBC";
+pub const VERSION: u8 = 1;
+
+/// Location and reason for a binary decode failure.
+/// `offset` is the byte position at which decoding failed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DecodeError {
+    pub offset: usize,
+    pub kind: DecodeErrorKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DecodeErrorKind {
+    BadMagic,
+    UnsupportedVersion(u8),
+    UnknownOpcode(u8),
+    InvalidBool(u8),
+    InvalidSlot(u8),
+    InvalidDirection(u8),
+    VarintOverflow,
+    UnexpectedEof,
+    TrailingBytes,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "byte {}: ", self.offset)?;
+        match &self.kind {
+            DecodeErrorKind::BadMagic => write!(f, "missing `LTBC` header"),
+            DecodeErrorKind::UnsupportedVersion(version) => write!(f, "unsupported format version {version}"),
+            DecodeErrorKind::UnknownOpcode(byte) => write!(f, "unknown opcode 0x{byte:02x}"),
+            DecodeErrorKind::InvalidBool(byte) => write!(f, "invalid bool byte 0x{byte:02x}"),
+            DecodeErrorKind::InvalidSlot(slot) => write!(f, "slot {slot} out of range"),
+            DecodeErrorKind::InvalidDirection(byte) => write!(f, "invalid direction byte 0x{byte:02x}"),
+            DecodeErrorKind::VarintOverflow => write!(f, "varint does not fit in 64 bits"),
+            DecodeErrorKind::UnexpectedEof => write!(f, "unexpected end of input"),
+            DecodeErrorKind::TrailingBytes => write!(f, "trailing bytes after last instruction"),
+        }
+    }
+}
+
+impl Error for DecodeError {}
+
+/// Encode a program in the binary format.
+///
+/// Layout:
+/// - 4 byte magic `LTBC`
+/// - 1 byte format version
+/// - instruction count as an unsigned LEB128 varint
+/// - per instruction, one opcode byte followed by its operand;
+///   ints are zigzag-encoded LEB128 varints, jump targets are unsigned LEB128 varints,
+///   bools are a single `0` or `1` byte, local slot and register numbers are a single byte,
+///   and directions are a single byte counting eighth turns clockwise from straight ahead
+pub fn encode(program: &Program) -> Vec<u8> {
+    let mut out = Vec::with_capacity(MAGIC.len() + 1 + program.len() * 2);
+    out.extend_from_slice(&MAGIC);
+    out.push(VERSION);
+    write_varint(&mut out, program.len() as u64);
+
+    for instruction in program.instructions() {
+        out.push(instruction.opcode().byte());
+        match instruction.operand() {
+            Operand::None => {}
+            Operand::Int(value) => write_varint(&mut out, zigzag_encode(value)),
+            Operand::Bool(value) => out.push(value as u8),
+            Operand::Address(target) => write_varint(&mut out, target as u64),
+            Operand::Slot(slot) => out.push(slot),
+            Operand::Direction(direction) => out.push(direction.byte()),
+        }
+    }
+
+    out
+}
+
+/// Decode a program from the binary format.
+pub fn decode(bytes: &[u8]) -> Result<Program, DecodeError> {
+    let mut reader = Reader { bytes, offset: 0 };
+
+    if reader.take(MAGIC.len())? != MAGIC {
+        return Err(DecodeError {
+            offset: 0,
+            kind: DecodeErrorKind::BadMagic,
+        });
+    }
+
+    let version = reader.byte()?;
+    if version != VERSION {
+        return Err(reader.error_at(reader.offset - 1, DecodeErrorKind::UnsupportedVersion(version)));
+    }
+
+    let count = reader.varint()?;
+    let mut instructions = Vec::with_capacity((count as usize).min(bytes.len()));
+    for _ in 0..count {
+        let start = reader.offset;
+        let byte = reader.byte()?;
+        let Some(opcode) = Opcode::from_byte(byte) else {
+            return Err(reader.error_at(start, DecodeErrorKind::UnknownOpcode(byte)));
+        };
+
+        let operand = match opcode.operand_kind() {
+            OperandKind::None => Operand::None,
+            OperandKind::Int => Operand::Int(zigzag_decode(reader.varint()?)),
+            OperandKind::Address => {
+                let start = reader.offset;
+                let target = reader.varint()?;
+                Operand::Address(usize::try_from(target).map_err(|_| reader.error_at(start, DecodeErrorKind::VarintOverflow))?)
+            }
+            OperandKind::Bool => match reader.byte()? {
+                0 => Operand::Bool(false),
+                1 => Operand::Bool(true),
+                other => return Err(reader.error_at(reader.offset - 1, DecodeErrorKind::InvalidBool(other))),
+            },
+            OperandKind::Slot => Operand::Slot(reader.byte()?),
+            OperandKind::Direction => {
+                let byte = reader.byte()?;
+                let Some(direction) = Direction::from_byte(byte) else {
+                    return Err(reader.error_at(reader.offset - 1, DecodeErrorKind::InvalidDirection(byte)));
+                };
+                Operand::Direction(direction)
+            }
+        };
+
+        // Only a slot number past the last slot can fail here
+        let Some(instruction) = Instruction::from_parts(opcode, operand) else {
+            let Operand::Slot(slot) = operand else {
+                unreachable!("{opcode} decoded with a mismatched operand");
+            };
+            return Err(reader.error_at(reader.offset - 1, DecodeErrorKind::InvalidSlot(slot)));
+        };
+        instructions.push(instruction);
+    }
+
+    if reader.offset != bytes.len() {
+        return Err(reader.error_at(reader.offset, DecodeErrorKind::TrailingBytes));
+    }
+
+    Ok(Program::new(instructions))
+}
+
+/// Decode a binary program and print it in the text format.
+pub fn disassemble(bytes: &[u8]) -> Result<String, DecodeError> {
+    decode(bytes).map(|program| text::format(&program))
+}
+
+// -- Helpers --
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn error_at(&self, offset: usize, kind: DecodeErrorKind) -> DecodeError {
+        DecodeError { offset, kind }
+    }
+
+    fn take(&mut self, len: usize) -> Result<&'a [u8], DecodeError> {
+        let Some(slice) = self.bytes.get(self.offset..self.offset + len) else {
+            return Err(self.error_at(self.bytes.len(), DecodeErrorKind::UnexpectedEof));
+        };
+        self.offset += len;
+        Ok(slice)
+    }
+
+    fn byte(&mut self) -> Result<u8, DecodeError> {
+        self.take(1).map(|slice| slice[0])
+    }
+
+    fn varint(&mut self) -> Result<u64, DecodeError> {
+        let start = self.offset;
+        let mut value = 0u64;
+        for shift in (0..64).step_by(7) {
+            let byte = self.byte()?;
+            let bits = (byte & 0x7f) as u64;
+            if shift == 63 && bits > 1 {
+                return Err(self.error_at(start, DecodeErrorKind::VarintOverflow));
+            }
+            value |= bits << shift;
+            if byte & 0x80 == 0 {
+                return Ok(value);
+            }
+        }
+        Err(self.error_at(start, DecodeErrorKind::VarintOverflow))
+    }
+}
+
+fn write_varint(out: &mut Vec<u8>, mut value: u64) {
+    loop {
+        let byte = (value & 0x7f) as u8;
+        value >>= 7;
+        if value == 0 {
+            out.push(byte);
+            return;
+        }
+        out.push(byte | 0x80);
+    }
+}
+
+#[inline]
+fn zigzag_encode(value: i64) -> u64 {
+    ((value << 1) ^ (value >> 63)) as u64
+}
+
+#[inline]
+fn zigzag_decode(value: u64) -> i64 {
+    ((value >> 1) as i64) ^ -((value & 1) as i64)
+}
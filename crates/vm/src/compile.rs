@@ -0,0 +1,287 @@
+use super::{direction::Direction, instruction::Instruction, value::Value, verify::VerifiedProgram};
+
+/// How `Vm::resume_executable` runs a verified program.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Tier {
+    /// Decode and dispatch each instruction as it runs.
+    Interpreted,
+    /// Resolve the program up front into threaded code: operands and jump targets baked in,
+    /// type checks dropped, and common sequences fused into single operations.
+    #[default]
+    Compiled,
+}
+
+/// A verified program, ready to run at its chosen tier.
+#[derive(Clone, Debug)]
+pub struct Executable {
+    program: VerifiedProgram,
+    ops: Option<Vec<Op>>,
+}
+
+impl Executable {
+    pub fn new(program: VerifiedProgram, tier: Tier) -> Self {
+        let ops = match tier {
+            Tier::Interpreted => None,
+            Tier::Compiled => Some(compile(&program)),
+        };
+        Self { program, ops }
+    }
+
+    // -- Getters --
+
+    #[inline]
+    pub fn program(&self) -> &VerifiedProgram {
+        &self.program
+    }
+
+    #[inline]
+    pub fn tier(&self) -> Tier {
+        match self.ops {
+            None => Tier::Interpreted,
+            Some(_) => Tier::Compiled,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn ops(&self) -> Option<&[Op]> {
+        self.ops.as_deref()
+    }
+}
+
+impl VerifiedProgram {
+    /// Prepare the program to run at `tier`.
+    pub fn into_executable(self, tier: Tier) -> Executable {
+        Executable::new(self, tier)
+    }
+}
+
+/// Compiled code starting at one instruction.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Op {
+    pub(crate) kind: OpKind,
+    /// Number of instructions the operation stands in for.
+    pub(crate) len: u64,
+    /// Where control goes afterwards, unless the operation jumps.
+    pub(crate) next: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum OpKind {
+    Push(Value),
+    Pop,
+    Dup,
+    Arithmetic(Arithmetic),
+    Neg,
+    /// `EQ` when true, `NE` when false.
+    Equal(bool),
+    Compare(Comparison),
+    Jump(usize),
+    Branch(Branch),
+    Call(usize),
+    Ret,
+    LoadLocal(u8),
+    StoreLocal(u8),
+    LoadRegister(u8),
+    StoreRegister(u8),
+    Read,
+    Sense(Direction),
+    CountMoore,
+    CountVonNeumann,
+    SenseTurmite(Direction),
+    Write,
+    Turn,
+    Move,
+    State,
+    SetState,
+    Halt,
+    /// `PUSH rhs`, arithmetic.
+    ArithmeticConst(Arithmetic, i64),
+    /// Comparison, conditional jump.
+    CompareBranch(Comparison, Branch),
+    /// `PUSH rhs`, comparison, conditional jump.
+    CompareConstBranch(Comparison, i64, Branch),
+    /// `READ`, `SENSE` or `STATE`, `PUSH rhs`, comparison, conditional jump.
+    SenseCompareBranch(Sense, Comparison, i64, Branch),
+    /// `PUSH value`, `WRITE`, with the value already known to be in range.
+    WriteConst(u8),
+    /// `PUSH state`, `SET_STATE`, with the state already known to be in range.
+    SetStateConst(u8),
+    /// `PUSH quarter_turns`, `TURN`.
+    TurnConst(i64),
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Arithmetic {
+    Add,
+    Sub,
+    Mul,
+}
+
+impl Arithmetic {
+    #[inline]
+    pub(crate) fn apply(self, lhs: i64, rhs: i64) -> Option<i64> {
+        match self {
+            Arithmetic::Add => lhs.checked_add(rhs),
+            Arithmetic::Sub => lhs.checked_sub(rhs),
+            Arithmetic::Mul => lhs.checked_mul(rhs),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Comparison {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Comparison {
+    #[inline]
+    pub(crate) fn apply(self, lhs: i64, rhs: i64) -> bool {
+        match self {
+            Comparison::Eq => lhs == rhs,
+            Comparison::Ne => lhs != rhs,
+            Comparison::Lt => lhs < rhs,
+            Comparison::Le => lhs <= rhs,
+            Comparison::Gt => lhs > rhs,
+            Comparison::Ge => lhs >= rhs,
+        }
+    }
+}
+
+/// Conditional jump to `target`, taken when the condition equals `when`.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct Branch {
+    pub(crate) when: bool,
+    pub(crate) target: usize,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub(crate) enum Sense {
+    Read,
+    Neighbour(Direction),
+    State,
+}
+
+// -- Helpers --
+
+/// Build an operation for every instruction, since a jump or a paused resume can land anywhere.
+fn compile(program: &VerifiedProgram) -> Vec<Op> {
+    let instructions = program.instructions();
+    let mut targets = vec![false; instructions.len()];
+    for instruction in instructions {
+        if let Instruction::Jump(target)
+        | Instruction::JumpIf(target)
+        | Instruction::JumpIfNot(target)
+        | Instruction::Call(target) = *instruction
+        {
+            targets[target] = true;
+        }
+    }
+
+    (0..instructions.len())
+        .map(|pc| {
+            // A fused sequence can't run on into an instruction that control can also reach by jumping
+            let end = (pc + 1..instructions.len())
+                .find(|&pc| targets[pc])
+                .unwrap_or(instructions.len());
+            let (kind, len) = fuse(&instructions[pc..end]).unwrap_or_else(|| (single(instructions[pc]), 1));
+            Op {
+                kind,
+                len: len as u64,
+                next: pc + len,
+            }
+        })
+        .collect()
+}
+
+/// Fused form of the sequence `window` begins with, and how many instructions it covers.
+fn fuse(window: &[Instruction]) -> Option<(OpKind, usize)> {
+    use Instruction::*;
+
+    Some(match *window {
+        [sense @ (Read | Instruction::Sense(_) | State), Push(rhs), compare, jump, ..] if branch(jump).is_some() => {
+            let sense = match sense {
+                Read => self::Sense::Read,
+                Instruction::Sense(direction) => self::Sense::Neighbour(direction),
+                _ => self::Sense::State,
+            };
+            (OpKind::SenseCompareBranch(sense, comparison(compare)?, rhs, branch(jump)?), 4)
+        }
+        [Push(rhs), compare, jump, ..] if branch(jump).is_some() => {
+            (OpKind::CompareConstBranch(comparison(compare)?, rhs, branch(jump)?), 3)
+        }
+        // Unlike the cases above, nothing says the operands of EQ and NE are ints
+        [compare @ (Lt | Le | Gt | Ge), jump, ..] => (OpKind::CompareBranch(comparison(compare)?, branch(jump)?), 2),
+        [Push(rhs), op, ..] => match op {
+            Add => (OpKind::ArithmeticConst(Arithmetic::Add, rhs), 2),
+            Sub => (OpKind::ArithmeticConst(Arithmetic::Sub, rhs), 2),
+            Mul => (OpKind::ArithmeticConst(Arithmetic::Mul, rhs), 2),
+            Write => (OpKind::WriteConst(u8::try_from(rhs).ok()?), 2),
+            SetState => (OpKind::SetStateConst(u8::try_from(rhs).ok()?), 2),
+            Turn => (OpKind::TurnConst(rhs), 2),
+            _ => return None,
+        },
+        _ => return None,
+    })
+}
+
+fn single(instruction: Instruction) -> OpKind {
+    match instruction {
+        Instruction::Push(value) => OpKind::Push(Value::Int(value)),
+        Instruction::PushBool(value) => OpKind::Push(Value::Bool(value)),
+        Instruction::Pop => OpKind::Pop,
+        Instruction::Dup => OpKind::Dup,
+        Instruction::Add => OpKind::Arithmetic(Arithmetic::Add),
+        Instruction::Sub => OpKind::Arithmetic(Arithmetic::Sub),
+        Instruction::Mul => OpKind::Arithmetic(Arithmetic::Mul),
+        Instruction::Neg => OpKind::Neg,
+        Instruction::Eq => OpKind::Equal(true),
+        Instruction::Ne => OpKind::Equal(false),
+        Instruction::Lt | Instruction::Le | Instruction::Gt | Instruction::Ge => {
+            OpKind::Compare(comparison(instruction).unwrap())
+        }
+        Instruction::Jump(target) => OpKind::Jump(target),
+        Instruction::JumpIf(_) | Instruction::JumpIfNot(_) => OpKind::Branch(branch(instruction).unwrap()),
+        Instruction::Call(target) => OpKind::Call(target),
+        Instruction::Ret => OpKind::Ret,
+        Instruction::LoadLocal(slot) => OpKind::LoadLocal(slot),
+        Instruction::StoreLocal(slot) => OpKind::StoreLocal(slot),
+        Instruction::LoadRegister(slot) => OpKind::LoadRegister(slot),
+        Instruction::StoreRegister(slot) => OpKind::StoreRegister(slot),
+        Instruction::Read => OpKind::Read,
+        Instruction::Sense(direction) => OpKind::Sense(direction),
+        Instruction::CountMoore => OpKind::CountMoore,
+        Instruction::CountVonNeumann => OpKind::CountVonNeumann,
+        Instruction::SenseTurmite(direction) => OpKind::SenseTurmite(direction),
+        Instruction::Write => OpKind::Write,
+        Instruction::Turn => OpKind::Turn,
+        Instruction::Move => OpKind::Move,
+        Instruction::State => OpKind::State,
+        Instruction::SetState => OpKind::SetState,
+        Instruction::Halt => OpKind::Halt,
+    }
+}
+
+fn comparison(instruction: Instruction) -> Option<Comparison> {
+    Some(match instruction {
+        Instruction::Eq => Comparison::Eq,
+        Instruction::Ne => Comparison::Ne,
+        Instruction::Lt => Comparison::Lt,
+        Instruction::Le => Comparison::Le,
+        Instruction::Gt => Comparison::Gt,
+        Instruction::Ge => Comparison::Ge,
+        _ => return None,
+    })
+}
+
+fn branch(instruction: Instruction) -> Option<Branch> {
+    Some(match instruction {
+        Instruction::JumpIf(target) => Branch { when: true, target },
+        Instruction::JumpIfNot(target) => Branch { when: false, target },
+        _ => return None,
+    })
+}
@@ -0,0 +1,80 @@
+use std::fmt;
+
+/// Neighbouring cell, relative to the turmite's position and heading.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Direction {
+    Ahead,
+    AheadRight,
+    Right,
+    BehindRight,
+    Behind,
+    BehindLeft,
+    Left,
+    AheadLeft,
+}
+
+impl Direction {
+    /// Every neighbour, clockwise from straight ahead.
+    pub const MOORE: [Direction; 8] = [
+        Direction::Ahead,
+        Direction::AheadRight,
+        Direction::Right,
+        Direction::BehindRight,
+        Direction::Behind,
+        Direction::BehindLeft,
+        Direction::Left,
+        Direction::AheadLeft,
+    ];
+
+    /// Orthogonal neighbours, clockwise from straight ahead.
+    pub const VON_NEUMANN: [Direction; 4] = [Direction::Ahead, Direction::Right, Direction::Behind, Direction::Left];
+
+    /// Cells forward and to the right of the turmite, negative for behind and to the left.
+    #[inline]
+    pub fn offset(self) -> (i64, i64) {
+        match self {
+            Direction::Ahead => (1, 0),
+            Direction::AheadRight => (1, 1),
+            Direction::Right => (0, 1),
+            Direction::BehindRight => (-1, 1),
+            Direction::Behind => (-1, 0),
+            Direction::BehindLeft => (-1, -1),
+            Direction::Left => (0, -1),
+            Direction::AheadLeft => (1, -1),
+        }
+    }
+
+    /// Name used by the text format.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Direction::Ahead => "AHEAD",
+            Direction::AheadRight => "AHEAD_RIGHT",
+            Direction::Right => "RIGHT",
+            Direction::BehindRight => "BEHIND_RIGHT",
+            Direction::Behind => "BEHIND",
+            Direction::BehindLeft => "BEHIND_LEFT",
+            Direction::Left => "LEFT",
+            Direction::AheadLeft => "AHEAD_LEFT",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::MOORE.into_iter().find(|direction| direction.name() == name)
+    }
+
+    /// Byte used by the binary format, counting eighth turns clockwise from straight ahead.
+    #[inline]
+    pub fn byte(&self) -> u8 {
+        *self as u8
+    }
+
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        Self::MOORE.get(byte as usize).copied()
+    }
+}
+
+impl fmt::Display for Direction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
@@ -0,0 +1,63 @@
+use std::{error::Error, fmt};
+
+use super::{instruction::Instruction, limits::Limit, value::ValueType};
+
+/// Reasons a program can fail at runtime.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VmError {
+    /// An instruction popped more values than were on the stack.
+    StackUnderflow { pc: usize, instruction: Instruction },
+    /// An operand had the wrong type.
+    TypeMismatch {
+        pc: usize,
+        instruction: Instruction,
+        expected: ValueType,
+        found: ValueType,
+    },
+    /// Integer arithmetic overflowed.
+    Overflow { pc: usize, instruction: Instruction },
+    /// A cell value or state was outside `0..=255`.
+    OutOfRange {
+        pc: usize,
+        instruction: Instruction,
+        value: i64,
+    },
+    /// A jump targeted an index past the end of the program.
+    InvalidJump { pc: usize, target: usize },
+    /// The program ran into one of the VM's `Limits`.
+    LimitExceeded { pc: usize, limit: Limit },
+    /// `RET` ran with no call to return from.
+    ReturnUnderflow { pc: usize },
+    /// A local slot or register number was out of range.
+    InvalidSlot { pc: usize, instruction: Instruction },
+    /// A board instruction was run without a board to act on.
+    NoBoard { pc: usize, instruction: Instruction },
+    /// Execution ran off the end of the program without reaching `HALT`.
+    MissingHalt { pc: usize },
+}
+
+impl fmt::Display for VmError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            VmError::StackUnderflow { pc, instruction } => write!(f, "stack underflow at {pc} ({instruction})"),
+            VmError::TypeMismatch {
+                pc,
+                instruction,
+                expected,
+                found,
+            } => write!(f, "type mismatch at {pc} ({instruction}): expected {expected}, found {found}"),
+            VmError::Overflow { pc, instruction } => write!(f, "integer overflow at {pc} ({instruction})"),
+            VmError::OutOfRange { pc, instruction, value } => {
+                write!(f, "value {value} out of range 0..=255 at {pc} ({instruction})")
+            }
+            VmError::InvalidJump { pc, target } => write!(f, "jump at {pc} targets missing instruction {target}"),
+            VmError::LimitExceeded { pc, limit } => write!(f, "{limit} limit exceeded at {pc}"),
+            VmError::ReturnUnderflow { pc } => write!(f, "RET at {pc} with no call to return from"),
+            VmError::InvalidSlot { pc, instruction } => write!(f, "no such slot at {pc} ({instruction})"),
+            VmError::NoBoard { pc, instruction } => write!(f, "no board attached at {pc} ({instruction})"),
+            VmError::MissingHalt { pc } => write!(f, "reached end of program at {pc} without HALT"),
+        }
+    }
+}
+
+impl Error for VmError {}
@@ -0,0 +1,71 @@
+use super::direction::Direction;
+
+/// Board and turmite that board-aware instructions act on.
+pub trait Host {
+    /// Value of the cell under the turmite.
+    fn read(&self) -> u8;
+
+    /// Value of the neighbouring cell in `direction`, wrapping at the board's edges the same way `advance` does.
+    fn sense(&self, direction: Direction) -> u8;
+
+    /// Whether another turmite stands on the neighbouring cell in `direction`.
+    fn sense_turmite(&self, direction: Direction) -> bool;
+
+    /// How many of the neighbouring cells in `directions` hold `value`.
+    fn count(&self, directions: &[Direction], value: i64) -> i64 {
+        directions
+            .iter()
+            .filter(|&&direction| self.sense(direction) as i64 == value)
+            .count() as i64
+    }
+
+    /// Overwrite the cell under the turmite.
+    fn write(&mut self, value: u8);
+
+    /// Rotate by a number of quarter turns, clockwise for positive values.
+    fn turn(&mut self, quarter_turns: i64);
+
+    /// Step one cell forward in the direction the turmite is facing.
+    fn advance(&mut self);
+
+    fn state(&self) -> u8;
+
+    fn set_state(&mut self, state: u8);
+}
+
+/// Stand-in host for programs run without a board, never constructed.
+pub(crate) enum Detached {}
+
+impl Host for Detached {
+    fn read(&self) -> u8 {
+        match *self {}
+    }
+
+    fn sense(&self, _direction: Direction) -> u8 {
+        match *self {}
+    }
+
+    fn sense_turmite(&self, _direction: Direction) -> bool {
+        match *self {}
+    }
+
+    fn write(&mut self, _value: u8) {
+        match *self {}
+    }
+
+    fn turn(&mut self, _quarter_turns: i64) {
+        match *self {}
+    }
+
+    fn advance(&mut self) {
+        match *self {}
+    }
+
+    fn state(&self) -> u8 {
+        match *self {}
+    }
+
+    fn set_state(&mut self, _state: u8) {
+        match *self {}
+    }
+}
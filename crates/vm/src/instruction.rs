@@ -0,0 +1,423 @@
+use std::fmt;
+
+use super::direction::Direction;
+
+/// Local slots available to the main program and to each call.
+pub const LOCALS: usize = 16;
+/// Registers shared by every call, which carry over when the program is reset.
+pub const REGISTERS: usize = 16;
+
+/// A single VM instruction.
+///
+/// The `Display` form matches the text format written by `Bytecode.encode()`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Instruction {
+    /// Push an integer constant.
+    Push(i64),
+    /// Push a boolean constant.
+    PushBool(bool),
+    /// Discard the top value.
+    Pop,
+    /// Push a copy of the top value.
+    Dup,
+    /// Pop two ints and push their sum.
+    Add,
+    /// Pop an int and push its negation.
+    Neg,
+    /// Pop two ints and push the first minus the second.
+    Sub,
+    /// Pop two ints and push their product.
+    Mul,
+    /// Pop two values of the same type and push whether they are equal.
+    Eq,
+    /// Pop two values of the same type and push whether they differ.
+    Ne,
+    /// Pop two ints and push whether the first is less than the second.
+    Lt,
+    /// Pop two ints and push whether the first is at most the second.
+    Le,
+    /// Pop two ints and push whether the first is greater than the second.
+    Gt,
+    /// Pop two ints and push whether the first is at least the second.
+    Ge,
+    /// Continue at the given instruction index.
+    Jump(usize),
+    /// Pop a bool and jump if it is true.
+    JumpIf(usize),
+    /// Pop a bool and jump if it is false.
+    JumpIfNot(usize),
+    /// Save the return address and a fresh set of locals, then continue at the given instruction index.
+    Call(usize),
+    /// Drop the current call's locals and continue after the `CALL` that made it.
+    Ret,
+    /// Push the int in the given local slot of the current call, zero until stored.
+    LoadLocal(u8),
+    /// Pop an int into the given local slot of the current call.
+    StoreLocal(u8),
+    /// Push the int in the given register. Registers start at zero and keep their values between steps.
+    LoadRegister(u8),
+    /// Pop an int into the given register.
+    StoreRegister(u8),
+    /// Push the value of the cell under the turmite.
+    Read,
+    /// Push the value of the neighbouring cell in the given direction.
+    Sense(Direction),
+    /// Pop an int and push how many of the eight neighbouring cells hold it.
+    CountMoore,
+    /// Pop an int and push how many of the four orthogonally neighbouring cells hold it.
+    CountVonNeumann,
+    /// Push whether another turmite stands on the neighbouring cell in the given direction.
+    SenseTurmite(Direction),
+    /// Pop an int in `0..=255` and write it to the cell under the turmite.
+    Write,
+    /// Pop an int and turn by that many quarter turns, clockwise for positive values.
+    Turn,
+    /// Step one cell forward, ending the turmite's current step.
+    Move,
+    /// Push the turmite's state.
+    State,
+    /// Pop an int in `0..=255` and set it as the turmite's state.
+    SetState,
+    /// Stop execution.
+    Halt,
+}
+
+impl Instruction {
+    /// Build an instruction from its opcode and operand.
+    /// Returns `None` if the operand does not match the opcode, or names a slot that doesn't exist.
+    pub fn from_parts(opcode: Opcode, operand: Operand) -> Option<Self> {
+        Some(match (opcode, operand) {
+            (Opcode::Push, Operand::Int(value)) => Instruction::Push(value),
+            (Opcode::PushBool, Operand::Bool(value)) => Instruction::PushBool(value),
+            (Opcode::Jump, Operand::Address(target)) => Instruction::Jump(target),
+            (Opcode::JumpIf, Operand::Address(target)) => Instruction::JumpIf(target),
+            (Opcode::JumpIfNot, Operand::Address(target)) => Instruction::JumpIfNot(target),
+            (Opcode::Call, Operand::Address(target)) => Instruction::Call(target),
+            (Opcode::LoadLocal, Operand::Slot(slot)) if (slot as usize) < LOCALS => Instruction::LoadLocal(slot),
+            (Opcode::StoreLocal, Operand::Slot(slot)) if (slot as usize) < LOCALS => Instruction::StoreLocal(slot),
+            (Opcode::LoadRegister, Operand::Slot(slot)) if (slot as usize) < REGISTERS => Instruction::LoadRegister(slot),
+            (Opcode::StoreRegister, Operand::Slot(slot)) if (slot as usize) < REGISTERS => Instruction::StoreRegister(slot),
+            (Opcode::Ret, Operand::None) => Instruction::Ret,
+            (Opcode::Sense, Operand::Direction(direction)) => Instruction::Sense(direction),
+            (Opcode::SenseTurmite, Operand::Direction(direction)) => Instruction::SenseTurmite(direction),
+            (Opcode::CountMoore, Operand::None) => Instruction::CountMoore,
+            (Opcode::CountVonNeumann, Operand::None) => Instruction::CountVonNeumann,
+            (Opcode::Pop, Operand::None) => Instruction::Pop,
+            (Opcode::Dup, Operand::None) => Instruction::Dup,
+            (Opcode::Add, Operand::None) => Instruction::Add,
+            (Opcode::Neg, Operand::None) => Instruction::Neg,
+            (Opcode::Sub, Operand::None) => Instruction::Sub,
+            (Opcode::Mul, Operand::None) => Instruction::Mul,
+            (Opcode::Eq, Operand::None) => Instruction::Eq,
+            (Opcode::Ne, Operand::None) => Instruction::Ne,
+            (Opcode::Lt, Operand::None) => Instruction::Lt,
+            (Opcode::Le, Operand::None) => Instruction::Le,
+            (Opcode::Gt, Operand::None) => Instruction::Gt,
+            (Opcode::Ge, Operand::None) => Instruction::Ge,
+            (Opcode::Read, Operand::None) => Instruction::Read,
+            (Opcode::Write, Operand::None) => Instruction::Write,
+            (Opcode::Turn, Operand::None) => Instruction::Turn,
+            (Opcode::Move, Operand::None) => Instruction::Move,
+            (Opcode::State, Operand::None) => Instruction::State,
+            (Opcode::SetState, Operand::None) => Instruction::SetState,
+            (Opcode::Halt, Operand::None) => Instruction::Halt,
+            _ => return None,
+        })
+    }
+
+    pub fn opcode(&self) -> Opcode {
+        match self {
+            Instruction::Push(_) => Opcode::Push,
+            Instruction::PushBool(_) => Opcode::PushBool,
+            Instruction::Pop => Opcode::Pop,
+            Instruction::Dup => Opcode::Dup,
+            Instruction::Add => Opcode::Add,
+            Instruction::Neg => Opcode::Neg,
+            Instruction::Sub => Opcode::Sub,
+            Instruction::Mul => Opcode::Mul,
+            Instruction::Eq => Opcode::Eq,
+            Instruction::Ne => Opcode::Ne,
+            Instruction::Lt => Opcode::Lt,
+            Instruction::Le => Opcode::Le,
+            Instruction::Gt => Opcode::Gt,
+            Instruction::Ge => Opcode::Ge,
+            Instruction::Jump(_) => Opcode::Jump,
+            Instruction::JumpIf(_) => Opcode::JumpIf,
+            Instruction::JumpIfNot(_) => Opcode::JumpIfNot,
+            Instruction::Call(_) => Opcode::Call,
+            Instruction::Ret => Opcode::Ret,
+            Instruction::LoadLocal(_) => Opcode::LoadLocal,
+            Instruction::StoreLocal(_) => Opcode::StoreLocal,
+            Instruction::LoadRegister(_) => Opcode::LoadRegister,
+            Instruction::StoreRegister(_) => Opcode::StoreRegister,
+            Instruction::Read => Opcode::Read,
+            Instruction::Sense(_) => Opcode::Sense,
+            Instruction::CountMoore => Opcode::CountMoore,
+            Instruction::CountVonNeumann => Opcode::CountVonNeumann,
+            Instruction::SenseTurmite(_) => Opcode::SenseTurmite,
+            Instruction::Write => Opcode::Write,
+            Instruction::Turn => Opcode::Turn,
+            Instruction::Move => Opcode::Move,
+            Instruction::State => Opcode::State,
+            Instruction::SetState => Opcode::SetState,
+            Instruction::Halt => Opcode::Halt,
+        }
+    }
+
+    pub fn operand(&self) -> Operand {
+        match *self {
+            Instruction::Push(value) => Operand::Int(value),
+            Instruction::PushBool(value) => Operand::Bool(value),
+            Instruction::Jump(target)
+            | Instruction::JumpIf(target)
+            | Instruction::JumpIfNot(target)
+            | Instruction::Call(target) => Operand::Address(target),
+            Instruction::LoadLocal(slot)
+            | Instruction::StoreLocal(slot)
+            | Instruction::LoadRegister(slot)
+            | Instruction::StoreRegister(slot) => Operand::Slot(slot),
+            Instruction::Sense(direction) | Instruction::SenseTurmite(direction) => Operand::Direction(direction),
+            _ => Operand::None,
+        }
+    }
+}
+
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.operand() {
+            Operand::None => write!(f, "{}", self.opcode()),
+            Operand::Int(value) => write!(f, "{} {}", self.opcode(), value),
+            Operand::Bool(value) => write!(f, "{} {}", self.opcode(), value),
+            Operand::Address(target) => write!(f, "{} {}", self.opcode(), target),
+            Operand::Slot(slot) => write!(f, "{} {}", self.opcode(), slot),
+            Operand::Direction(direction) => write!(f, "{} {}", self.opcode(), direction),
+        }
+    }
+}
+
+/// Instruction tag without its operand.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Opcode {
+    Push,
+    PushBool,
+    Pop,
+    Dup,
+    Add,
+    Neg,
+    Sub,
+    Mul,
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+    Jump,
+    JumpIf,
+    JumpIfNot,
+    Call,
+    Ret,
+    LoadLocal,
+    StoreLocal,
+    LoadRegister,
+    StoreRegister,
+    Read,
+    Sense,
+    CountMoore,
+    CountVonNeumann,
+    SenseTurmite,
+    Write,
+    Turn,
+    Move,
+    State,
+    SetState,
+    Halt,
+}
+
+impl Opcode {
+    pub const ALL: [Opcode; 34] = [
+        Opcode::Push,
+        Opcode::PushBool,
+        Opcode::Pop,
+        Opcode::Dup,
+        Opcode::Add,
+        Opcode::Neg,
+        Opcode::Sub,
+        Opcode::Mul,
+        Opcode::Eq,
+        Opcode::Ne,
+        Opcode::Lt,
+        Opcode::Le,
+        Opcode::Gt,
+        Opcode::Ge,
+        Opcode::Jump,
+        Opcode::JumpIf,
+        Opcode::JumpIfNot,
+        Opcode::Call,
+        Opcode::Ret,
+        Opcode::LoadLocal,
+        Opcode::StoreLocal,
+        Opcode::LoadRegister,
+        Opcode::StoreRegister,
+        Opcode::Read,
+        Opcode::Sense,
+        Opcode::CountMoore,
+        Opcode::CountVonNeumann,
+        Opcode::SenseTurmite,
+        Opcode::Write,
+        Opcode::Turn,
+        Opcode::Move,
+        Opcode::State,
+        Opcode::SetState,
+        Opcode::Halt,
+    ];
+
+    /// Mnemonic used by the text format.
+    pub fn name(&self) -> &'static str {
+        match self {
+            Opcode::Push => "PUSH",
+            Opcode::PushBool => "PUSH_BOOL",
+            Opcode::Pop => "POP",
+            Opcode::Dup => "DUP",
+            Opcode::Add => "ADD",
+            Opcode::Neg => "NEG",
+            Opcode::Sub => "SUB",
+            Opcode::Mul => "MUL",
+            Opcode::Eq => "EQ",
+            Opcode::Ne => "NE",
+            Opcode::Lt => "LT",
+            Opcode::Le => "LE",
+            Opcode::Gt => "GT",
+            Opcode::Ge => "GE",
+            Opcode::Jump => "JUMP",
+            Opcode::JumpIf => "JUMP_IF",
+            Opcode::JumpIfNot => "JUMP_IF_NOT",
+            Opcode::Call => "CALL",
+            Opcode::Ret => "RET",
+            Opcode::LoadLocal => "LOAD_LOCAL",
+            Opcode::StoreLocal => "STORE_LOCAL",
+            Opcode::LoadRegister => "LOAD_REG",
+            Opcode::StoreRegister => "STORE_REG",
+            Opcode::Read => "READ",
+            Opcode::Sense => "SENSE",
+            Opcode::CountMoore => "COUNT_MOORE",
+            Opcode::CountVonNeumann => "COUNT_VON_NEUMANN",
+            Opcode::SenseTurmite => "SENSE_TURMITE",
+            Opcode::Write => "WRITE",
+            Opcode::Turn => "TURN",
+            Opcode::Move => "MOVE",
+            Opcode::State => "STATE",
+            Opcode::SetState => "SET_STATE",
+            Opcode::Halt => "HALT",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|opcode| opcode.name() == name)
+    }
+
+    /// Opcode byte used by the binary format.
+    /// Values are part of the format, so existing ones must never change.
+    pub fn byte(&self) -> u8 {
+        match self {
+            Opcode::Halt => 0x00,
+            Opcode::Push => 0x01,
+            Opcode::PushBool => 0x02,
+            Opcode::Pop => 0x03,
+            Opcode::Dup => 0x04,
+            Opcode::Add => 0x10,
+            Opcode::Neg => 0x11,
+            Opcode::Sub => 0x12,
+            Opcode::Mul => 0x13,
+            Opcode::Eq => 0x20,
+            Opcode::Ne => 0x21,
+            Opcode::Lt => 0x22,
+            Opcode::Le => 0x23,
+            Opcode::Gt => 0x24,
+            Opcode::Ge => 0x25,
+            Opcode::Jump => 0x30,
+            Opcode::JumpIf => 0x31,
+            Opcode::JumpIfNot => 0x32,
+            Opcode::Call => 0x33,
+            Opcode::Ret => 0x34,
+            Opcode::Read => 0x40,
+            Opcode::Write => 0x41,
+            Opcode::Turn => 0x42,
+            Opcode::Move => 0x43,
+            Opcode::State => 0x44,
+            Opcode::SetState => 0x45,
+            Opcode::Sense => 0x46,
+            Opcode::CountMoore => 0x47,
+            Opcode::CountVonNeumann => 0x48,
+            Opcode::SenseTurmite => 0x49,
+            Opcode::LoadLocal => 0x50,
+            Opcode::StoreLocal => 0x51,
+            Opcode::LoadRegister => 0x52,
+            Opcode::StoreRegister => 0x53,
+        }
+    }
+
+    pub fn from_byte(byte: u8) -> Option<Self> {
+        Self::ALL.into_iter().find(|opcode| opcode.byte() == byte)
+    }
+
+    /// Kind of operand this opcode carries.
+    pub fn operand_kind(&self) -> OperandKind {
+        match self {
+            Opcode::Push => OperandKind::Int,
+            Opcode::PushBool => OperandKind::Bool,
+            Opcode::Jump | Opcode::JumpIf | Opcode::JumpIfNot | Opcode::Call => OperandKind::Address,
+            Opcode::LoadLocal | Opcode::StoreLocal | Opcode::LoadRegister | Opcode::StoreRegister => OperandKind::Slot,
+            Opcode::Sense | Opcode::SenseTurmite => OperandKind::Direction,
+            _ => OperandKind::None,
+        }
+    }
+
+    /// Whether the instruction needs a board and turmite to act on.
+    pub fn is_board_op(&self) -> bool {
+        matches!(
+            self,
+            Opcode::Read
+                | Opcode::Write
+                | Opcode::Turn
+                | Opcode::Move
+                | Opcode::State
+                | Opcode::SetState
+                | Opcode::Sense
+                | Opcode::CountMoore
+                | Opcode::CountVonNeumann
+                | Opcode::SenseTurmite
+        )
+    }
+}
+
+impl fmt::Display for Opcode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Immediate operand of an instruction.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operand {
+    None,
+    Int(i64),
+    Bool(bool),
+    /// Instruction index targeted by a jump or call.
+    Address(usize),
+    /// Local slot or register number.
+    Slot(u8),
+    /// Neighbour to sense.
+    Direction(Direction),
+}
+
+/// Operand shape expected by an opcode.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OperandKind {
+    None,
+    Int,
+    Bool,
+    Address,
+    Slot,
+    Direction,
+}
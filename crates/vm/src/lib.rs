@@ -0,0 +1,31 @@
+pub mod binary;
+mod compile;
+mod direction;
+mod error;
+mod host;
+mod instruction;
+mod limits;
+mod optimise;
+mod program;
+pub mod text;
+mod value;
+mod verify;
+mod vm;
+
+pub mod prelude {
+    pub use super::{
+        binary::{DecodeError, DecodeErrorKind},
+        compile::{Executable, Tier},
+        direction::Direction,
+        error::VmError,
+        host::Host,
+        instruction::{Instruction, Opcode, Operand, OperandKind},
+        limits::{Limit, Limits},
+        optimise::OptLevel,
+        program::Program,
+        text::{ParseError, ParseErrorKind},
+        value::{Value, ValueType},
+        verify::{VerifiedProgram, VerifyError, VerifyErrorKind},
+        vm::{Exit, Vm},
+    };
+}
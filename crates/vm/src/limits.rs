@@ -0,0 +1,49 @@
+use std::fmt;
+
+/// Resources a program may use, enforced by `Vm`. Exceeding one fails with `VmError::LimitExceeded`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Limits {
+    /// Instructions a program may run between moves, counting the `MOVE` itself.
+    pub step_instructions: u64,
+    /// Values the stack may hold at once.
+    pub stack_depth: usize,
+    /// Calls that may be active at once.
+    pub call_depth: usize,
+    /// Instructions a program may run over the life of the `Vm`.
+    pub fuel: u64,
+}
+
+impl Default for Limits {
+    fn default() -> Self {
+        Self {
+            step_instructions: u64::MAX,
+            stack_depth: 4096,
+            call_depth: 256,
+            fuel: u64::MAX,
+        }
+    }
+}
+
+/// Which of the `Limits` a program ran into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Limit {
+    StepInstructions,
+    StackDepth,
+    CallDepth,
+    Fuel,
+}
+
+impl Limit {
+    pub const ALL: [Limit; 4] = [Limit::StepInstructions, Limit::StackDepth, Limit::CallDepth, Limit::Fuel];
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::StepInstructions => write!(f, "instructions per step"),
+            Limit::StackDepth => write!(f, "stack depth"),
+            Limit::CallDepth => write!(f, "call depth"),
+            Limit::Fuel => write!(f, "fuel"),
+        }
+    }
+}
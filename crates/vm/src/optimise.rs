@@ -0,0 +1,191 @@
+use super::{instruction::Instruction, program::Program};
+
+/// How hard `Program::optimise` works.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord)]
+pub enum OptLevel {
+    /// Leave the program as it is.
+    None,
+    /// Remove `NEG NEG` pairs, jumps to the next instruction and unreachable code.
+    Basic,
+    /// As `Basic`, plus fold constant arithmetic, comparisons and branches.
+    #[default]
+    Full,
+}
+
+impl Program {
+    /// Rewrite the program into a shorter one with the same result, repeating the passes until nothing changes.
+    ///
+    /// Jump targets are renumbered to match. Only programs that run without error are guaranteed to behave
+    /// the same: folding never hides an overflow, but removing `NEG NEG` does, so `--x` no longer fails for `i64::MIN`.
+    pub fn optimise(&self, level: OptLevel) -> Program {
+        let mut instructions = self.instructions().to_vec();
+        if level == OptLevel::None {
+            return Program::new(instructions);
+        }
+
+        loop {
+            let mut changed = false;
+            changed |= rewrite(&mut instructions, remove_double_negation);
+            if level == OptLevel::Full {
+                changed |= rewrite(&mut instructions, fold_constants);
+            }
+            changed |= remove_dead_code(&mut instructions);
+
+            if !changed {
+                return Program::new(instructions);
+            }
+        }
+    }
+}
+
+// -- Helpers --
+
+/// Replacement for each instruction, where `None` deletes it.
+type Slots = Vec<Option<Instruction>>;
+
+/// A peephole pass. Given the instructions from some position onwards, returns the window
+/// length it matched and what to replace the window with.
+type Peephole = fn(&[Instruction]) -> Option<(usize, Vec<Instruction>)>;
+
+/// Apply `pass` at every position, never letting a window swallow a jump target,
+/// since control can arrive there with a different stack.
+fn rewrite(instructions: &mut Vec<Instruction>, pass: Peephole) -> bool {
+    let targets = jump_targets(instructions);
+    let mut slots: Slots = instructions.iter().copied().map(Some).collect();
+    let mut changed = false;
+
+    let mut pc = 0;
+    while pc < instructions.len() {
+        let matched = pass(&instructions[pc..]).filter(|(len, _)| (pc + 1..pc + len).all(|pc| !targets[pc]));
+        let Some((len, replacement)) = matched else {
+            pc += 1;
+            continue;
+        };
+
+        debug_assert!(replacement.len() <= len);
+        for (offset, slot) in slots[pc..pc + len].iter_mut().enumerate() {
+            *slot = replacement.get(offset).copied();
+        }
+        changed = true;
+        pc += len;
+    }
+
+    if changed {
+        *instructions = compact(slots);
+    }
+    changed
+}
+
+fn remove_double_negation(window: &[Instruction]) -> Option<(usize, Vec<Instruction>)> {
+    match window {
+        [Instruction::Neg, Instruction::Neg, ..] => Some((2, Vec::new())),
+        _ => None,
+    }
+}
+
+fn fold_constants(window: &[Instruction]) -> Option<(usize, Vec<Instruction>)> {
+    use Instruction::*;
+
+    let folded = match *window {
+        [Push(lhs), Push(rhs), op, ..] => match op {
+            Add => Push(lhs.checked_add(rhs)?),
+            Sub => Push(lhs.checked_sub(rhs)?),
+            Mul => Push(lhs.checked_mul(rhs)?),
+            Eq => PushBool(lhs == rhs),
+            Ne => PushBool(lhs != rhs),
+            Lt => PushBool(lhs < rhs),
+            Le => PushBool(lhs <= rhs),
+            Gt => PushBool(lhs > rhs),
+            Ge => PushBool(lhs >= rhs),
+            _ => return None,
+        },
+        [PushBool(lhs), PushBool(rhs), op @ (Eq | Ne), ..] => PushBool((lhs == rhs) == (op == Eq)),
+        [Push(value), Neg, ..] => return Some((2, vec![Push(value.checked_neg()?)])),
+        [Push(_) | PushBool(_), Pop, ..] => return Some((2, Vec::new())),
+        [PushBool(condition), JumpIf(target) | JumpIfNot(target), ..] => {
+            let taken = condition == matches!(window[1], JumpIf(_));
+            return Some((2, if taken { vec![Jump(target)] } else { Vec::new() }));
+        }
+        _ => return None,
+    };
+    Some((3, vec![folded]))
+}
+
+/// Delete instructions no path from the start reaches, and jumps to the very next instruction.
+fn remove_dead_code(instructions: &mut Vec<Instruction>) -> bool {
+    let len = instructions.len();
+    let mut reachable = vec![false; len];
+    let mut pending = vec![0];
+    while let Some(pc) = pending.pop() {
+        if pc >= len || reachable[pc] {
+            continue;
+        }
+        reachable[pc] = true;
+        match instructions[pc] {
+            Instruction::Jump(target) => pending.push(target),
+            // Assume every call returns, since subroutines aren't followed to their `RET`
+            Instruction::JumpIf(target) | Instruction::JumpIfNot(target) | Instruction::Call(target) => {
+                pending.extend([target, pc + 1])
+            }
+            Instruction::Halt | Instruction::Ret => {}
+            _ => pending.push(pc + 1),
+        }
+    }
+
+    let slots: Slots = instructions
+        .iter()
+        .enumerate()
+        .map(|(pc, &instruction)| match instruction {
+            Instruction::Jump(target) if target == pc + 1 && target < len => None,
+            _ => reachable[pc].then_some(instruction),
+        })
+        .collect();
+
+    let changed = slots.iter().any(Option::is_none);
+    if changed {
+        *instructions = compact(slots);
+    }
+    changed
+}
+
+/// Drop deleted slots and renumber jumps. A jump to a deleted instruction lands on the next one kept.
+fn compact(slots: Slots) -> Vec<Instruction> {
+    let mut new_pc = Vec::with_capacity(slots.len() + 1);
+    let mut kept = 0;
+    for slot in &slots {
+        new_pc.push(kept);
+        kept += slot.is_some() as usize;
+    }
+    new_pc.push(kept);
+
+    // Targets past the end stay past the end, so the VM still reports them
+    let remap = |target: usize| new_pc.get(target).copied().unwrap_or_else(|| target - slots.len() + kept);
+    slots
+        .iter()
+        .flatten()
+        .map(|&instruction| match instruction {
+            Instruction::Jump(target) => Instruction::Jump(remap(target)),
+            Instruction::JumpIf(target) => Instruction::JumpIf(remap(target)),
+            Instruction::JumpIfNot(target) => Instruction::JumpIfNot(remap(target)),
+            Instruction::Call(target) => Instruction::Call(remap(target)),
+            other => other,
+        })
+        .collect()
+}
+
+fn jump_targets(instructions: &[Instruction]) -> Vec<bool> {
+    let mut targets = vec![false; instructions.len()];
+    for instruction in instructions {
+        let (Instruction::Jump(target)
+        | Instruction::JumpIf(target)
+        | Instruction::JumpIfNot(target)
+        | Instruction::Call(target)) = *instruction
+        else {
+            continue;
+        };
+        if let Some(target) = targets.get_mut(target) {
+            *target = true;
+        }
+    }
+    targets
+}
@@ -0,0 +1,217 @@
+use std::{collections::HashMap, error::Error, fmt};
+
+use super::{
+    direction::Direction,
+    instruction::{Instruction, Opcode, Operand, OperandKind},
+    program::Program,
+};
+
+/// Location and reason for a text parse failure.
+/// Lines and columns are 1-based, columns count characters.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ParseError {
+    pub line: usize,
+    pub column: usize,
+    pub kind: ParseErrorKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ParseErrorKind {
+    /// The mnemonic is not part of the instruction set.
+    UnknownOpcode(String),
+    /// The opcode requires an argument but none was given.
+    MissingArgument { opcode: Opcode },
+    /// The opcode takes no argument but one was given.
+    UnexpectedArgument { opcode: Opcode },
+    /// The argument could not be parsed as the expected type.
+    InvalidArgument { opcode: Opcode, argument: String },
+    /// A label name isn't a letter or `_` followed by letters, digits or `_`.
+    InvalidLabel(String),
+    /// The same label is defined twice.
+    DuplicateLabel(String),
+    /// A jump or call names a label that isn't defined anywhere.
+    UndefinedLabel(String),
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: ", self.line, self.column)?;
+        match &self.kind {
+            ParseErrorKind::UnknownOpcode(opcode) => write!(f, "unknown opcode `{opcode}`"),
+            ParseErrorKind::MissingArgument { opcode } => write!(f, "{opcode} requires an argument"),
+            ParseErrorKind::UnexpectedArgument { opcode } => write!(f, "{opcode} takes no argument"),
+            ParseErrorKind::InvalidArgument { opcode, argument } => {
+                write!(f, "invalid argument `{argument}` for {opcode}")
+            }
+            ParseErrorKind::InvalidLabel(label) => write!(f, "invalid label `{label}`"),
+            ParseErrorKind::DuplicateLabel(label) => write!(f, "label `{label}` is already defined"),
+            ParseErrorKind::UndefinedLabel(label) => write!(f, "undefined label `{label}`"),
+        }
+    }
+}
+
+impl Error for ParseError {}
+
+/// Parse a program from the text format written by `Bytecode.encode()`.
+///
+/// Each line holds one instruction, an opcode optionally followed by one argument:
+///
+/// ```text
+/// ;; expr: 2 + -3
+/// PUSH 2
+/// PUSH 3
+/// NEG
+/// ADD
+/// HALT
+/// ```
+///
+/// A line can start with a label naming the instruction that follows, which jumps and calls can then
+/// target by name instead of by index:
+///
+/// ```text
+/// loop: READ
+///       CALL flip
+///       JUMP loop
+/// flip:
+///       ...
+///       RET
+/// ```
+///
+/// Anything after a `;` is a comment, and blank lines are ignored.
+pub fn parse(src: &str) -> Result<Program, ParseError> {
+    let labels = find_labels(src)?;
+    let mut instructions = Vec::new();
+    for (index, line) in src.lines().enumerate() {
+        if let Some(instruction) = parse_line(index + 1, line, &labels)? {
+            instructions.push(instruction);
+        }
+    }
+    Ok(Program::new(instructions))
+}
+
+/// Write a program in the text format, one instruction per line.
+pub fn format(program: &Program) -> String {
+    let mut out = String::new();
+    for instruction in program.instructions() {
+        out.push_str(&instruction.to_string());
+        out.push('\n');
+    }
+    out
+}
+
+// -- Helpers --
+
+/// Index of the instruction each label names, which may be one past the last if nothing follows it.
+fn find_labels(src: &str) -> Result<HashMap<&str, usize>, ParseError> {
+    let mut labels = HashMap::new();
+    let mut count = 0;
+    for (index, text) in src.lines().enumerate() {
+        let mut tokens = tokenise(text.split(';').next().unwrap_or_default()).peekable();
+        if let Some((column, label)) = tokens.next_if(|(_, token)| token.ends_with(':')) {
+            let error = |kind| ParseError {
+                line: index + 1,
+                column,
+                kind,
+            };
+            let label = &label[..label.len() - 1];
+            if !is_identifier(label) {
+                return Err(error(ParseErrorKind::InvalidLabel(label.to_string())));
+            }
+            if labels.insert(label, count).is_some() {
+                return Err(error(ParseErrorKind::DuplicateLabel(label.to_string())));
+            }
+        }
+        count += tokens.next().is_some() as usize;
+    }
+    Ok(labels)
+}
+
+fn parse_line(line: usize, text: &str, labels: &HashMap<&str, usize>) -> Result<Option<Instruction>, ParseError> {
+    let error = |column, kind| ParseError { line, column, kind };
+
+    let code = text.split(';').next().unwrap_or_default();
+    let mut tokens = tokenise(code).peekable();
+    // Labels were already collected
+    tokens.next_if(|(_, token)| token.ends_with(':'));
+
+    let Some((opcode_column, name)) = tokens.next() else {
+        return Ok(None);
+    };
+    let Some(opcode) = Opcode::from_name(name) else {
+        return Err(error(opcode_column, ParseErrorKind::UnknownOpcode(name.to_string())));
+    };
+
+    let invalid = |column, argument: &str| {
+        error(
+            column,
+            ParseErrorKind::InvalidArgument {
+                opcode,
+                argument: argument.to_string(),
+            },
+        )
+    };
+
+    let argument = tokens.next();
+    let operand = match (opcode.operand_kind(), argument) {
+        (OperandKind::None, None) => Operand::None,
+        (OperandKind::None, Some((column, _))) => {
+            return Err(error(column, ParseErrorKind::UnexpectedArgument { opcode }));
+        }
+        (_, None) => return Err(error(opcode_column, ParseErrorKind::MissingArgument { opcode })),
+        (OperandKind::Address, Some((column, label))) if is_identifier(label) => match labels.get(label) {
+            Some(&target) => Operand::Address(target),
+            None => return Err(error(column, ParseErrorKind::UndefinedLabel(label.to_string()))),
+        },
+        (kind, Some((column, argument))) => parse_operand(kind, argument).ok_or_else(|| invalid(column, argument))?,
+    };
+
+    if let Some((column, _)) = tokens.next() {
+        return Err(error(column, ParseErrorKind::UnexpectedArgument { opcode }));
+    }
+
+    // Only a slot number past the last slot can fail here
+    match (Instruction::from_parts(opcode, operand), argument) {
+        (Some(instruction), _) => Ok(Some(instruction)),
+        (None, Some((column, argument))) => Err(invalid(column, argument)),
+        (None, None) => unreachable!("{opcode} parsed without its operand"),
+    }
+}
+
+fn parse_operand(kind: OperandKind, argument: &str) -> Option<Operand> {
+    match kind {
+        OperandKind::None => None,
+        OperandKind::Int => argument.parse().ok().map(Operand::Int),
+        OperandKind::Address => argument.parse().ok().map(Operand::Address),
+        OperandKind::Slot => argument.parse().ok().map(Operand::Slot),
+        OperandKind::Direction => Direction::from_name(argument).map(Operand::Direction),
+        OperandKind::Bool => match argument {
+            "true" => Some(Operand::Bool(true)),
+            "false" => Some(Operand::Bool(false)),
+            _ => None,
+        },
+    }
+}
+
+fn is_identifier(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_') && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Whitespace-separated tokens paired with their 1-based character column.
+fn tokenise(code: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut rest = code;
+    let mut column = 1;
+    std::iter::from_fn(move || {
+        let trimmed = rest.trim_start();
+        column += rest[..rest.len() - trimmed.len()].chars().count();
+        if trimmed.is_empty() {
+            return None;
+        }
+        let end = trimmed.find(char::is_whitespace).unwrap_or(trimmed.len());
+        let (token, tail) = trimmed.split_at(end);
+        let start = column;
+        column += token.chars().count();
+        rest = tail;
+        Some((start, token))
+    })
+}
@@ -0,0 +1,44 @@
+use std::fmt;
+
+/// A single stack value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Value {
+    Int(i64),
+    Bool(bool),
+}
+
+impl Value {
+    /// Type tag of this value.
+    #[inline]
+    pub fn kind(&self) -> ValueType {
+        match self {
+            Value::Int(_) => ValueType::Int,
+            Value::Bool(_) => ValueType::Bool,
+        }
+    }
+}
+
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Value::Int(value) => write!(f, "{value}"),
+            Value::Bool(value) => write!(f, "{value}"),
+        }
+    }
+}
+
+/// Type tag of a stack value.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ValueType {
+    Int,
+    Bool,
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValueType::Int => write!(f, "int"),
+            ValueType::Bool => write!(f, "bool"),
+        }
+    }
+}
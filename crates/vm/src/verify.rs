@@ -0,0 +1,369 @@
+use std::{collections::BTreeMap, error::Error, fmt, ops::Deref};
+
+use super::{
+    instruction::{Instruction, LOCALS, REGISTERS},
+    program::Program,
+    value::ValueType,
+};
+
+/// A program that has passed `Program::verify`.
+///
+/// Every reachable instruction sees the same stack depth and value types on every path,
+/// no instruction can underflow the stack or receive an operand of the wrong type,
+/// every jump lands inside the program and every path ends in `HALT`.
+///
+/// Each `CALL` target starts a subroutine. Every call to it must be made with the same stack, every `RET`
+/// in it must leave the same stack, and no instruction can belong to more than one subroutine or to both
+/// a subroutine and the main program.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifiedProgram {
+    program: Program,
+    stack_types: Vec<Option<Vec<ValueType>>>,
+    /// Entry of the subroutine each reachable instruction belongs to, `None` for the main program.
+    subroutines: Vec<Option<usize>>,
+    /// For each subroutine entry, where its calls return to and the subroutine each call was made from.
+    return_sites: BTreeMap<usize, Vec<(usize, Option<usize>)>>,
+    max_depth: usize,
+}
+
+impl VerifiedProgram {
+    // -- Getters --
+
+    #[inline]
+    pub fn program(&self) -> &Program {
+        &self.program
+    }
+
+    /// Stack value types, bottom first, on entry to the instruction at `pc`.
+    /// `None` if the instruction is unreachable.
+    #[inline]
+    pub fn stack_types(&self, pc: usize) -> Option<&[ValueType]> {
+        self.stack_types.get(pc)?.as_deref()
+    }
+
+    /// Entry of the subroutine the instruction at `pc` belongs to, or `None` for the main program
+    /// and unreachable instructions.
+    #[inline]
+    pub fn subroutine(&self, pc: usize) -> Option<usize> {
+        self.subroutines.get(pc).copied().flatten()
+    }
+
+    /// If the subroutine starting at `entry` is called to return to `return_pc`, the subroutine that call is made from.
+    pub(crate) fn caller(&self, entry: usize, return_pc: usize) -> Option<Option<usize>> {
+        let sites = self.return_sites.get(&entry)?;
+        sites.iter().find(|(site, _)| *site == return_pc).map(|&(_, caller)| caller)
+    }
+
+    /// Deepest the stack can get while running the program.
+    #[inline]
+    pub fn max_depth(&self) -> usize {
+        self.max_depth
+    }
+
+    pub fn into_program(self) -> Program {
+        self.program
+    }
+}
+
+impl Deref for VerifiedProgram {
+    type Target = Program;
+
+    fn deref(&self) -> &Program {
+        &self.program
+    }
+}
+
+/// Location and reason for a verification failure.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifyError {
+    pub pc: usize,
+    pub kind: VerifyErrorKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VerifyErrorKind {
+    /// The instruction could pop more values than are on the stack.
+    StackUnderflow { instruction: Instruction },
+    /// The instruction could receive an operand of the wrong type.
+    TypeMismatch {
+        instruction: Instruction,
+        expected: ValueType,
+        found: ValueType,
+    },
+    /// A jump or call targets an index past the end of the program.
+    InvalidJump { target: usize },
+    /// A local slot or register number is out of range.
+    InvalidSlot { instruction: Instruction },
+    /// `RET` can run outside any subroutine.
+    ReturnFromMain,
+    /// The instruction can be reached from two different subroutines, or from a subroutine and the main program.
+    SharedCode { expected: Option<usize>, found: Option<usize> },
+    /// Two paths reach the instruction with different stacks.
+    StackMismatch {
+        expected: Vec<ValueType>,
+        found: Vec<ValueType>,
+    },
+    /// Execution can run off the end of the program without reaching `HALT`.
+    MissingHalt,
+}
+
+impl fmt::Display for VerifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "instruction {}: ", self.pc)?;
+        match &self.kind {
+            VerifyErrorKind::StackUnderflow { instruction } => write!(f, "{instruction} can underflow the stack"),
+            VerifyErrorKind::TypeMismatch {
+                instruction,
+                expected,
+                found,
+            } => write!(f, "{instruction} expects {expected}, found {found}"),
+            VerifyErrorKind::InvalidJump { target } => write!(f, "jump targets missing instruction {target}"),
+            VerifyErrorKind::InvalidSlot { instruction } => write!(f, "{instruction} names a missing slot"),
+            VerifyErrorKind::ReturnFromMain => write!(f, "RET can run outside a subroutine"),
+            VerifyErrorKind::SharedCode { expected, found } => {
+                write!(f, "reached from {} and {}", format_owner(*expected), format_owner(*found))
+            }
+            VerifyErrorKind::StackMismatch { expected, found } => {
+                write!(f, "reached with stack {} and {}", format_stack(expected), format_stack(found))
+            }
+            VerifyErrorKind::MissingHalt => write!(f, "execution can run off the end without HALT"),
+        }
+    }
+}
+
+impl Error for VerifyError {}
+
+impl Program {
+    /// Check the stack depth and value types along every control flow path.
+    pub fn verify(self) -> Result<VerifiedProgram, VerifyError> {
+        verify(self)
+    }
+}
+
+fn verify(program: Program) -> Result<VerifiedProgram, VerifyError> {
+    let len = program.len();
+    let mut stack_types: Vec<Option<Vec<ValueType>>> = vec![None; len];
+    let mut subroutines = vec![None; len];
+    let mut return_sites: BTreeMap<usize, Vec<(usize, Option<usize>)>> = BTreeMap::new();
+    // Stack left by each subroutine's `RET`, once one has been reached
+    let mut returns: BTreeMap<usize, Vec<ValueType>> = BTreeMap::new();
+    let mut max_depth = 0;
+
+    let mut pending = vec![(0, Vec::new(), None)];
+    while let Some((pc, stack, subroutine)) = pending.pop() {
+        if pc >= len {
+            return Err(VerifyError {
+                pc,
+                kind: VerifyErrorKind::MissingHalt,
+            });
+        }
+
+        // Only walk each instruction once, checking every other path agrees with the first
+        match &stack_types[pc] {
+            Some(_) if subroutines[pc] != subroutine => {
+                return Err(VerifyError {
+                    pc,
+                    kind: VerifyErrorKind::SharedCode {
+                        expected: subroutines[pc],
+                        found: subroutine,
+                    },
+                });
+            }
+            Some(expected) if *expected == stack => continue,
+            Some(expected) => {
+                return Err(VerifyError {
+                    pc,
+                    kind: VerifyErrorKind::StackMismatch {
+                        expected: expected.clone(),
+                        found: stack,
+                    },
+                });
+            }
+            None => {
+                stack_types[pc] = Some(stack.clone());
+                subroutines[pc] = subroutine;
+            }
+        }
+
+        let mut stack = stack;
+        let instruction = program.instructions()[pc];
+        let mut checker = Checker {
+            pc,
+            instruction,
+            stack: &mut stack,
+        };
+        let mut successors = [Some(pc + 1), None];
+
+        match instruction {
+            Instruction::Push(_) | Instruction::Read | Instruction::Sense(_) | Instruction::State => {
+                checker.push(ValueType::Int)
+            }
+            Instruction::SenseTurmite(_) => checker.push(ValueType::Bool),
+            Instruction::PushBool(_) => checker.push(ValueType::Bool),
+            Instruction::Pop => {
+                checker.pop()?;
+            }
+            Instruction::Dup => {
+                let kind = checker.pop()?;
+                checker.push(kind);
+                checker.push(kind);
+            }
+            Instruction::Add | Instruction::Sub | Instruction::Mul => {
+                checker.pop_expect(ValueType::Int)?;
+                checker.pop_expect(ValueType::Int)?;
+                checker.push(ValueType::Int);
+            }
+            Instruction::Neg | Instruction::CountMoore | Instruction::CountVonNeumann => {
+                checker.pop_expect(ValueType::Int)?;
+                checker.push(ValueType::Int);
+            }
+            Instruction::Eq | Instruction::Ne => {
+                let rhs = checker.pop()?;
+                let lhs = checker.pop()?;
+                checker.expect(lhs, rhs)?;
+                checker.push(ValueType::Bool);
+            }
+            Instruction::Lt | Instruction::Le | Instruction::Gt | Instruction::Ge => {
+                checker.pop_expect(ValueType::Int)?;
+                checker.pop_expect(ValueType::Int)?;
+                checker.push(ValueType::Bool);
+            }
+            Instruction::Jump(target) => successors = [Some(checker.target(target, len)?), None],
+            Instruction::JumpIf(target) | Instruction::JumpIfNot(target) => {
+                checker.pop_expect(ValueType::Bool)?;
+                successors[1] = Some(checker.target(target, len)?);
+            }
+            Instruction::Call(target) => {
+                let target = checker.target(target, len)?;
+                successors = [None, None];
+                return_sites.entry(target).or_default().push((pc + 1, subroutine));
+                if let Some(returned) = returns.get(&target) {
+                    pending.push((pc + 1, returned.clone(), subroutine));
+                }
+                pending.push((target, checker.stack.clone(), Some(target)));
+            }
+            Instruction::Ret => {
+                let Some(entry) = subroutine else {
+                    return Err(checker.error(VerifyErrorKind::ReturnFromMain));
+                };
+                successors = [None, None];
+                match returns.get(&entry) {
+                    Some(expected) if expected != checker.stack => {
+                        return Err(checker.error(VerifyErrorKind::StackMismatch {
+                            expected: expected.clone(),
+                            found: checker.stack.clone(),
+                        }));
+                    }
+                    Some(_) => {}
+                    None => {
+                        returns.insert(entry, checker.stack.clone());
+                        for &(site, caller) in &return_sites[&entry] {
+                            pending.push((site, checker.stack.clone(), caller));
+                        }
+                    }
+                }
+            }
+            Instruction::LoadLocal(slot) | Instruction::LoadRegister(slot) => {
+                checker.slot(slot)?;
+                checker.push(ValueType::Int);
+            }
+            Instruction::StoreLocal(slot) | Instruction::StoreRegister(slot) => {
+                checker.slot(slot)?;
+                checker.pop_expect(ValueType::Int)?;
+            }
+            Instruction::Write | Instruction::Turn | Instruction::SetState => {
+                checker.pop_expect(ValueType::Int)?;
+            }
+            Instruction::Move => {}
+            Instruction::Halt => successors = [None, None],
+        }
+
+        max_depth = max_depth.max(stack.len());
+        for next in successors.into_iter().flatten() {
+            pending.push((next, stack.clone(), subroutine));
+        }
+    }
+
+    Ok(VerifiedProgram {
+        program,
+        stack_types,
+        subroutines,
+        return_sites,
+        max_depth,
+    })
+}
+
+// -- Helpers --
+
+/// Abstract stack for a single instruction.
+struct Checker<'a> {
+    pc: usize,
+    instruction: Instruction,
+    stack: &'a mut Vec<ValueType>,
+}
+
+impl Checker<'_> {
+    fn error(&self, kind: VerifyErrorKind) -> VerifyError {
+        VerifyError { pc: self.pc, kind }
+    }
+
+    fn push(&mut self, kind: ValueType) {
+        self.stack.push(kind);
+    }
+
+    fn pop(&mut self) -> Result<ValueType, VerifyError> {
+        self.stack.pop().ok_or_else(|| {
+            self.error(VerifyErrorKind::StackUnderflow {
+                instruction: self.instruction,
+            })
+        })
+    }
+
+    fn pop_expect(&mut self, expected: ValueType) -> Result<(), VerifyError> {
+        let found = self.pop()?;
+        self.expect(expected, found)
+    }
+
+    fn expect(&self, expected: ValueType, found: ValueType) -> Result<(), VerifyError> {
+        if found != expected {
+            return Err(self.error(VerifyErrorKind::TypeMismatch {
+                instruction: self.instruction,
+                expected,
+                found,
+            }));
+        }
+        Ok(())
+    }
+
+    fn slot(&self, slot: u8) -> Result<(), VerifyError> {
+        let count = match self.instruction {
+            Instruction::LoadLocal(_) | Instruction::StoreLocal(_) => LOCALS,
+            _ => REGISTERS,
+        };
+        if slot as usize >= count {
+            return Err(self.error(VerifyErrorKind::InvalidSlot {
+                instruction: self.instruction,
+            }));
+        }
+        Ok(())
+    }
+
+    fn target(&self, target: usize, len: usize) -> Result<usize, VerifyError> {
+        if target >= len {
+            return Err(self.error(VerifyErrorKind::InvalidJump { target }));
+        }
+        Ok(target)
+    }
+}
+
+fn format_owner(subroutine: Option<usize>) -> String {
+    match subroutine {
+        Some(entry) => format!("the subroutine at {entry}"),
+        None => "the main program".to_string(),
+    }
+}
+
+fn format_stack(stack: &[ValueType]) -> String {
+    let kinds: Vec<String> = stack.iter().map(ValueType::to_string).collect();
+    format!("[{}]", kinds.join(", "))
+}
@@ -0,0 +1,726 @@
+use super::{
+    compile::{Executable, OpKind, Sense},
+    direction::Direction,
+    error::VmError,
+    host::{Detached, Host},
+    instruction::{self, Instruction},
+    limits::{Limit, Limits},
+    program::Program,
+    value::{Value, ValueType},
+    verify::VerifiedProgram,
+};
+
+/// Why `Vm::resume` returned control to the caller.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Exit {
+    /// The program executed `MOVE`.
+    Moved,
+    /// The program reached `HALT`. Resuming again returns immediately.
+    Halted,
+    /// The instruction budget ran out before the program moved or halted.
+    /// Resuming continues from where it stopped.
+    OutOfBudget,
+}
+
+/// Stack machine state.
+#[derive(Clone, Debug)]
+pub struct Vm {
+    pc: usize,
+    stack: Vec<Value>,
+    /// Return address of each active call, outermost first.
+    frames: Vec<usize>,
+    /// `LOCALS` slots for the main program, followed by the same again for each active call.
+    locals: Vec<i64>,
+    registers: [i64; Vm::REGISTERS],
+    limits: Limits,
+    /// Instructions run over the life of the `Vm`.
+    executed: u64,
+    /// Instructions run since the last `MOVE` or `HALT`.
+    since_move: u64,
+}
+
+impl Default for Vm {
+    fn default() -> Self {
+        Self {
+            pc: 0,
+            stack: Vec::new(),
+            frames: Vec::new(),
+            locals: vec![0; Vm::LOCALS],
+            registers: [0; Vm::REGISTERS],
+            limits: Limits::default(),
+            executed: 0,
+            since_move: 0,
+        }
+    }
+}
+
+impl Vm {
+    /// Local slots available to the main program and to each call.
+    pub const LOCALS: usize = instruction::LOCALS;
+    pub const REGISTERS: usize = instruction::REGISTERS;
+
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_limits(limits: Limits) -> Self {
+        Self {
+            limits,
+            ..Self::default()
+        }
+    }
+
+    // -- Getters --
+
+    #[inline]
+    pub fn pc(&self) -> usize {
+        self.pc
+    }
+
+    #[inline]
+    pub fn stack(&self) -> &[Value] {
+        &self.stack
+    }
+
+    /// Return address of each active call, outermost first.
+    #[inline]
+    pub fn frames(&self) -> &[usize] {
+        &self.frames
+    }
+
+    /// Local slots of the innermost active call, or of the main program outside any call.
+    #[inline]
+    pub fn locals(&self) -> &[i64] {
+        &self.locals[self.locals.len() - Self::LOCALS..]
+    }
+
+    #[inline]
+    pub fn registers(&self) -> &[i64; Vm::REGISTERS] {
+        &self.registers
+    }
+
+    #[inline]
+    pub fn limits(&self) -> &Limits {
+        &self.limits
+    }
+
+    /// Instructions run over the life of the `Vm`, which is what `Limits::fuel` caps.
+    #[inline]
+    pub fn executed(&self) -> u64 {
+        self.executed
+    }
+
+    /// Instructions run since the program last moved or halted, which is what `Limits::step_instructions` caps.
+    #[inline]
+    pub fn since_move(&self) -> u64 {
+        self.since_move
+    }
+
+    /// Change the limits. Fuel already used still counts against the new `fuel` limit.
+    pub fn set_limits(&mut self, limits: Limits) {
+        self.limits = limits;
+    }
+
+    // -- Execution --
+
+    /// Clear the stack, calls and locals, and rewind to the first instruction.
+    /// Registers, limits and fuel used carry over into a restarted or replaced program.
+    pub fn reset(&mut self) {
+        self.pc = 0;
+        self.since_move = 0;
+        self.stack.clear();
+        self.frames.clear();
+        self.locals.clear();
+        self.locals.resize(Self::LOCALS, 0);
+    }
+
+    /// Zero every register.
+    pub fn clear_registers(&mut self) {
+        self.registers = [0; Vm::REGISTERS];
+    }
+
+    /// Run `program` from the start until `HALT`, without a board.
+    /// Returns the value left on top of the stack, if any.
+    pub fn run(&mut self, program: &Program) -> Result<Option<Value>, VmError> {
+        self.reset();
+        match self.metered(u64::MAX, |vm, budget| vm.interpret::<true, Detached>(program, None, budget))? {
+            Exit::Halted => Ok(self.stack.last().copied()),
+            Exit::Moved => unreachable!("MOVE requires a board"),
+            Exit::OutOfBudget => unreachable!("unlimited budget ran out"),
+        }
+    }
+
+    /// Continue `program` from the current instruction against `host`, until it moves or halts.
+    /// The stack and program counter carry over between calls, so a program can loop forever, yielding at each `MOVE`.
+    pub fn resume<H: Host>(&mut self, program: &Program, host: &mut H) -> Result<Exit, VmError> {
+        self.resume_with_budget(program, host, u64::MAX)
+    }
+
+    /// As `resume`, but give up after executing `budget` instructions.
+    /// Unlike running into `Limits::step_instructions`, running out of budget isn't an error.
+    pub fn resume_with_budget<H: Host>(&mut self, program: &Program, host: &mut H, budget: u64) -> Result<Exit, VmError> {
+        self.metered(budget, |vm, budget| vm.interpret::<true, H>(program, Some(host), budget))
+    }
+
+    /// As `resume_with_budget`, but without reporting the stack, type and jump errors the verifier has already ruled out.
+    /// Jump targets and slots go unchecked, while popped values are still matched on, treating a mismatch as a bug
+    /// rather than an error. Arithmetic overflow, out of range cell values and every limit but stack depth are still
+    /// checked.
+    ///
+    /// If the current stack doesn't match what the verifier expects at `pc`, for example because this `Vm`
+    /// was last used with a different program, or the program could outgrow `Limits::stack_depth`,
+    /// this falls back to the checked path.
+    pub fn resume_verified<H: Host>(&mut self, program: &VerifiedProgram, host: &mut H, budget: u64) -> Result<Exit, VmError> {
+        self.metered(budget, |vm, budget| vm.interpret_verified(program, host, budget))
+    }
+
+    /// As `resume_verified`, running the program at the tier it was prepared for.
+    /// Every tier gives the same result, leaving the stack and program counter in the same state.
+    pub fn resume_executable<H: Host>(&mut self, executable: &Executable, host: &mut H, budget: u64) -> Result<Exit, VmError> {
+        self.metered(budget, |vm, budget| vm.run_compiled(executable, host, budget))
+    }
+
+    /// Run `run` with a budget cut short by the step and fuel limits, counting the instructions it takes.
+    /// `run` counts its budget down as it goes.
+    #[inline(always)]
+    fn metered(
+        &mut self,
+        budget: u64,
+        run: impl FnOnce(&mut Self, &mut u64) -> Result<Exit, VmError>,
+    ) -> Result<Exit, VmError> {
+        // Stop where a limit falls within the budget, preferring the limit on a tie, so hitting it is reported
+        let (allowed, limit) = [
+            (
+                self.limits.step_instructions.saturating_sub(self.since_move),
+                Some(Limit::StepInstructions),
+            ),
+            (self.limits.fuel.saturating_sub(self.executed), Some(Limit::Fuel)),
+            (budget, None),
+        ]
+        .into_iter()
+        .min_by_key(|(left, _)| *left)
+        .unwrap();
+
+        let mut left = allowed;
+        let exit = run(self, &mut left);
+        let ran = allowed - left;
+        self.executed += ran;
+        self.since_move += ran;
+
+        match (&exit, limit) {
+            (Ok(Exit::Moved | Exit::Halted), _) => self.since_move = 0,
+            (Ok(Exit::OutOfBudget), Some(limit)) => return Err(VmError::LimitExceeded { pc: self.pc, limit }),
+            _ => {}
+        }
+        exit
+    }
+
+    fn interpret_verified<H: Host>(
+        &mut self,
+        program: &VerifiedProgram,
+        host: &mut H,
+        budget: &mut u64,
+    ) -> Result<Exit, VmError> {
+        if !self.matches(program) || program.max_depth() > self.limits.stack_depth {
+            return self.interpret::<true, H>(program, Some(host), budget);
+        }
+        self.stack.reserve(program.max_depth().saturating_sub(self.stack.len()));
+        self.interpret::<false, H>(program, Some(host), budget)
+    }
+
+    fn run_compiled<H: Host>(&mut self, executable: &Executable, host: &mut H, budget: &mut u64) -> Result<Exit, VmError> {
+        let program = executable.program();
+        let Some(ops) = executable
+            .ops()
+            .filter(|_| self.matches(program) && program.max_depth() <= self.limits.stack_depth)
+        else {
+            return self.interpret_verified(program, host, budget);
+        };
+
+        while *budget > 0 {
+            let op = &ops[self.pc];
+            if op.len > *budget {
+                // Not enough budget left for the whole fused sequence, so stop partway through it like the interpreter would
+                return self.interpret_verified(program, host, budget);
+            }
+
+            // Operations that could fail leave everything untouched, then let the interpreter rerun them to report the error
+            let mut next = op.next;
+            match op.kind {
+                OpKind::Push(value) => self.stack.push(value),
+                OpKind::Pop => {
+                    self.stack.pop();
+                }
+                OpKind::Dup => self.stack.push(self.peek(0)),
+                OpKind::Arithmetic(arithmetic) => {
+                    let Some(result) = arithmetic.apply(self.peek_int(1), self.peek_int(0)) else {
+                        return self.interpret_verified(program, host, budget);
+                    };
+                    self.stack.pop();
+                    self.replace_top(Value::Int(result));
+                }
+                OpKind::Neg => {
+                    let Some(result) = self.peek_int(0).checked_neg() else {
+                        return self.interpret_verified(program, host, budget);
+                    };
+                    self.replace_top(Value::Int(result));
+                }
+                OpKind::Equal(equal) => {
+                    let rhs = self.stack.pop().unwrap();
+                    self.replace_top(Value::Bool((self.peek(0) == rhs) == equal));
+                }
+                OpKind::Compare(comparison) => {
+                    let result = comparison.apply(self.peek_int(1), self.peek_int(0));
+                    self.stack.pop();
+                    self.replace_top(Value::Bool(result));
+                }
+                OpKind::Jump(target) => next = target,
+                OpKind::Branch(branch) => {
+                    let Some(Value::Bool(condition)) = self.stack.pop() else {
+                        unreachable!("verified program mistyped at {}", self.pc);
+                    };
+                    if condition == branch.when {
+                        next = branch.target;
+                    }
+                }
+                OpKind::Call(target) => {
+                    if self.frames.len() >= self.limits.call_depth {
+                        return self.interpret_verified(program, host, budget);
+                    }
+                    self.enter(next);
+                    next = target;
+                }
+                OpKind::Ret => {
+                    let Some(return_pc) = self.leave() else {
+                        unreachable!("verified program returned from main at {}", self.pc);
+                    };
+                    next = return_pc;
+                }
+                OpKind::LoadLocal(slot) => {
+                    let value = self.locals()[slot as usize];
+                    self.stack.push(Value::Int(value));
+                }
+                OpKind::StoreLocal(slot) => {
+                    let value = self.peek_int(0);
+                    self.stack.pop();
+                    let base = self.locals.len() - Self::LOCALS;
+                    self.locals[base + slot as usize] = value;
+                }
+                OpKind::LoadRegister(slot) => self.stack.push(Value::Int(self.registers[slot as usize])),
+                OpKind::StoreRegister(slot) => {
+                    self.registers[slot as usize] = self.peek_int(0);
+                    self.stack.pop();
+                }
+                OpKind::Read => self.stack.push(Value::Int(host.read() as i64)),
+                OpKind::Sense(direction) => self.stack.push(Value::Int(host.sense(direction) as i64)),
+                OpKind::CountMoore | OpKind::CountVonNeumann => {
+                    let directions: &[Direction] = match op.kind {
+                        OpKind::CountMoore => &Direction::MOORE,
+                        _ => &Direction::VON_NEUMANN,
+                    };
+                    let count = host.count(directions, self.peek_int(0));
+                    self.replace_top(Value::Int(count));
+                }
+                OpKind::SenseTurmite(direction) => self.stack.push(Value::Bool(host.sense_turmite(direction))),
+                OpKind::Write | OpKind::SetState => {
+                    let Ok(value) = u8::try_from(self.peek_int(0)) else {
+                        return self.interpret_verified(program, host, budget);
+                    };
+                    self.stack.pop();
+                    match op.kind {
+                        OpKind::Write => host.write(value),
+                        _ => host.set_state(value),
+                    }
+                }
+                OpKind::Turn => {
+                    let quarter_turns = self.peek_int(0);
+                    self.stack.pop();
+                    host.turn(quarter_turns);
+                }
+                OpKind::Move => {
+                    host.advance();
+                    self.pc = next;
+                    *budget -= op.len;
+                    return Ok(Exit::Moved);
+                }
+                OpKind::State => self.stack.push(Value::Int(host.state() as i64)),
+                OpKind::Halt => {
+                    *budget -= op.len;
+                    return Ok(Exit::Halted);
+                }
+                OpKind::ArithmeticConst(arithmetic, rhs) => {
+                    let Some(result) = arithmetic.apply(self.peek_int(0), rhs) else {
+                        return self.interpret_verified(program, host, budget);
+                    };
+                    self.replace_top(Value::Int(result));
+                }
+                OpKind::CompareBranch(comparison, branch) => {
+                    let result = comparison.apply(self.peek_int(1), self.peek_int(0));
+                    self.stack.truncate(self.stack.len() - 2);
+                    if result == branch.when {
+                        next = branch.target;
+                    }
+                }
+                OpKind::CompareConstBranch(comparison, rhs, branch) => {
+                    let result = comparison.apply(self.peek_int(0), rhs);
+                    self.stack.pop();
+                    if result == branch.when {
+                        next = branch.target;
+                    }
+                }
+                OpKind::SenseCompareBranch(sense, comparison, rhs, branch) => {
+                    let lhs = match sense {
+                        Sense::Read => host.read(),
+                        Sense::Neighbour(direction) => host.sense(direction),
+                        Sense::State => host.state(),
+                    };
+                    if comparison.apply(lhs as i64, rhs) == branch.when {
+                        next = branch.target;
+                    }
+                }
+                OpKind::WriteConst(value) => host.write(value),
+                OpKind::SetStateConst(state) => host.set_state(state),
+                OpKind::TurnConst(quarter_turns) => host.turn(quarter_turns),
+            }
+
+            self.pc = next;
+            *budget -= op.len;
+        }
+        Ok(Exit::OutOfBudget)
+    }
+
+    /// Interpret instructions one at a time, counting each against `budget`, including one that fails.
+    fn interpret<const CHECKED: bool, H: Host>(
+        &mut self,
+        program: &Program,
+        mut host: Option<&mut H>,
+        budget: &mut u64,
+    ) -> Result<Exit, VmError> {
+        while *budget > 0 {
+            *budget -= 1;
+            match self.step::<CHECKED, H>(program, host.as_deref_mut())? {
+                Flow::Continue => {}
+                Flow::Moved => return Ok(Exit::Moved),
+                Flow::Halted => return Ok(Exit::Halted),
+            }
+        }
+        Ok(Exit::OutOfBudget)
+    }
+
+    /// Execute the instruction at `pc`.
+    /// On error, `pc` is left pointing at the failing instruction, but any operands it popped stay popped, so the
+    /// stack is not the one it started with and resuming from there is not meaningful.
+    ///
+    /// With `CHECKED` off, the program must be verified and the stack must match the verifier's types at `pc`.
+    #[inline(always)]
+    fn step<const CHECKED: bool, H: Host>(&mut self, program: &Program, host: Option<&mut H>) -> Result<Flow, VmError> {
+        let pc = self.pc;
+        let Some(&instruction) = program.get(pc) else {
+            return Err(VmError::MissingHalt { pc });
+        };
+
+        if instruction.opcode().is_board_op() && host.is_none() {
+            return Err(VmError::NoBoard { pc, instruction });
+        }
+
+        let mut next = pc + 1;
+        let mut flow = Flow::Continue;
+
+        match instruction {
+            Instruction::Push(value) => self.stack.push(Value::Int(value)),
+            Instruction::PushBool(value) => self.stack.push(Value::Bool(value)),
+            Instruction::Pop => {
+                self.pop::<CHECKED>(instruction)?;
+            }
+            Instruction::Dup => {
+                let value = self.pop::<CHECKED>(instruction)?;
+                self.stack.extend([value, value]);
+            }
+            Instruction::Add => self.int_binary::<CHECKED>(instruction, i64::checked_add)?,
+            Instruction::Sub => self.int_binary::<CHECKED>(instruction, i64::checked_sub)?,
+            Instruction::Mul => self.int_binary::<CHECKED>(instruction, i64::checked_mul)?,
+            Instruction::Neg => {
+                let value = self.pop_int::<CHECKED>(instruction)?;
+                let neg = value.checked_neg().ok_or(VmError::Overflow { pc, instruction })?;
+                self.stack.push(Value::Int(neg));
+            }
+            Instruction::Eq | Instruction::Ne => {
+                let rhs = self.pop::<CHECKED>(instruction)?;
+                let lhs = self.pop::<CHECKED>(instruction)?;
+                if CHECKED && lhs.kind() != rhs.kind() {
+                    return Err(VmError::TypeMismatch {
+                        pc,
+                        instruction,
+                        expected: lhs.kind(),
+                        found: rhs.kind(),
+                    });
+                }
+                self.stack.push(Value::Bool((lhs == rhs) == (instruction == Instruction::Eq)));
+            }
+            Instruction::Lt => self.int_compare::<CHECKED>(instruction, |lhs, rhs| lhs < rhs)?,
+            Instruction::Le => self.int_compare::<CHECKED>(instruction, |lhs, rhs| lhs <= rhs)?,
+            Instruction::Gt => self.int_compare::<CHECKED>(instruction, |lhs, rhs| lhs > rhs)?,
+            Instruction::Ge => self.int_compare::<CHECKED>(instruction, |lhs, rhs| lhs >= rhs)?,
+            Instruction::Jump(target) => next = self.jump_target::<CHECKED>(program, target)?,
+            Instruction::JumpIf(target) | Instruction::JumpIfNot(target) => {
+                let target = self.jump_target::<CHECKED>(program, target)?;
+                if self.pop_bool::<CHECKED>(instruction)? == matches!(instruction, Instruction::JumpIf(_)) {
+                    next = target;
+                }
+            }
+            Instruction::Call(target) => {
+                next = self.jump_target::<CHECKED>(program, target)?;
+                if self.frames.len() >= self.limits.call_depth {
+                    return Err(VmError::LimitExceeded {
+                        pc,
+                        limit: Limit::CallDepth,
+                    });
+                }
+                self.enter(pc + 1);
+            }
+            Instruction::Ret => {
+                next = match self.leave() {
+                    Some(return_pc) => return_pc,
+                    None if CHECKED => return Err(VmError::ReturnUnderflow { pc }),
+                    None => unreachable!("verified program returned from main at {pc}"),
+                };
+            }
+            Instruction::LoadLocal(slot) => {
+                let index = self.local_index::<CHECKED>(instruction, slot)?;
+                self.stack.push(Value::Int(self.locals[index]));
+            }
+            Instruction::StoreLocal(slot) => {
+                let index = self.local_index::<CHECKED>(instruction, slot)?;
+                self.locals[index] = self.pop_int::<CHECKED>(instruction)?;
+            }
+            Instruction::LoadRegister(slot) => {
+                let index = self.register_index::<CHECKED>(instruction, slot)?;
+                self.stack.push(Value::Int(self.registers[index]));
+            }
+            Instruction::StoreRegister(slot) => {
+                let index = self.register_index::<CHECKED>(instruction, slot)?;
+                self.registers[index] = self.pop_int::<CHECKED>(instruction)?;
+            }
+            Instruction::Read => {
+                let host = host.unwrap();
+                self.stack.push(Value::Int(host.read() as i64));
+            }
+            Instruction::Sense(direction) => {
+                let host = host.unwrap();
+                self.stack.push(Value::Int(host.sense(direction) as i64));
+            }
+            Instruction::CountMoore | Instruction::CountVonNeumann => {
+                let value = self.pop_int::<CHECKED>(instruction)?;
+                let directions: &[Direction] = match instruction {
+                    Instruction::CountMoore => &Direction::MOORE,
+                    _ => &Direction::VON_NEUMANN,
+                };
+                self.stack.push(Value::Int(host.unwrap().count(directions, value)));
+            }
+            Instruction::SenseTurmite(direction) => {
+                let host = host.unwrap();
+                self.stack.push(Value::Bool(host.sense_turmite(direction)));
+            }
+            Instruction::Write => {
+                let value = self.pop_u8::<CHECKED>(instruction)?;
+                host.unwrap().write(value);
+            }
+            Instruction::Turn => {
+                let quarter_turns = self.pop_int::<CHECKED>(instruction)?;
+                host.unwrap().turn(quarter_turns);
+            }
+            Instruction::Move => {
+                host.unwrap().advance();
+                flow = Flow::Moved;
+            }
+            Instruction::State => {
+                let host = host.unwrap();
+                self.stack.push(Value::Int(host.state() as i64));
+            }
+            Instruction::SetState => {
+                let state = self.pop_u8::<CHECKED>(instruction)?;
+                host.unwrap().set_state(state);
+            }
+            Instruction::Halt => return Ok(Flow::Halted),
+        }
+
+        // Verified programs only run unchecked when they can't outgrow the stack
+        if CHECKED && self.stack.len() > self.limits.stack_depth {
+            return Err(VmError::LimitExceeded {
+                pc,
+                limit: Limit::StackDepth,
+            });
+        }
+
+        self.pc = next;
+        Ok(flow)
+    }
+
+    // -- Helpers --
+
+    /// Whether the stack has the types the verifier expects at `pc`,
+    /// and each active call returns somewhere the verifier knows its subroutine returns to.
+    fn matches(&self, program: &VerifiedProgram) -> bool {
+        let stack_matches = program.stack_types(self.pc).is_some_and(|types| {
+            types.len() == self.stack.len() && types.iter().zip(&self.stack).all(|(kind, value)| *kind == value.kind())
+        });
+        if !stack_matches {
+            return false;
+        }
+
+        let mut subroutine = program.subroutine(self.pc);
+        for &return_pc in self.frames.iter().rev() {
+            match subroutine.and_then(|entry| program.caller(entry, return_pc)) {
+                Some(caller) => subroutine = caller,
+                None => return false,
+            }
+        }
+        subroutine.is_none()
+    }
+
+    /// Start a call that returns to `return_pc`.
+    #[inline]
+    fn enter(&mut self, return_pc: usize) {
+        self.frames.push(return_pc);
+        self.locals.resize(self.locals.len() + Self::LOCALS, 0);
+    }
+
+    /// Finish the innermost call, returning where it was made from.
+    #[inline]
+    fn leave(&mut self) -> Option<usize> {
+        let return_pc = self.frames.pop()?;
+        self.locals.truncate(self.locals.len() - Self::LOCALS);
+        Some(return_pc)
+    }
+
+    #[inline]
+    fn local_index<const CHECKED: bool>(&self, instruction: Instruction, slot: u8) -> Result<usize, VmError> {
+        let slot = self.slot::<CHECKED>(instruction, slot, Self::LOCALS)?;
+        Ok(self.locals.len() - Self::LOCALS + slot)
+    }
+
+    #[inline]
+    fn register_index<const CHECKED: bool>(&self, instruction: Instruction, slot: u8) -> Result<usize, VmError> {
+        self.slot::<CHECKED>(instruction, slot, Self::REGISTERS)
+    }
+
+    #[inline]
+    fn slot<const CHECKED: bool>(&self, instruction: Instruction, slot: u8, count: usize) -> Result<usize, VmError> {
+        if CHECKED && slot as usize >= count {
+            return Err(VmError::InvalidSlot {
+                pc: self.pc,
+                instruction,
+            });
+        }
+        Ok(slot as usize)
+    }
+
+    /// Value `depth` places below the top of a verified program's stack.
+    #[inline]
+    fn peek(&self, depth: usize) -> Value {
+        self.stack[self.stack.len() - 1 - depth]
+    }
+
+    #[inline]
+    fn peek_int(&self, depth: usize) -> i64 {
+        match self.peek(depth) {
+            Value::Int(value) => value,
+            Value::Bool(_) => unreachable!("verified program mistyped at {}", self.pc),
+        }
+    }
+
+    #[inline]
+    fn replace_top(&mut self, value: Value) {
+        *self.stack.last_mut().unwrap() = value;
+    }
+
+    #[inline]
+    fn pop<const CHECKED: bool>(&mut self, instruction: Instruction) -> Result<Value, VmError> {
+        match self.stack.pop() {
+            Some(value) => Ok(value),
+            None if CHECKED => Err(VmError::StackUnderflow {
+                pc: self.pc,
+                instruction,
+            }),
+            None => unreachable!("verified program underflowed at {}", self.pc),
+        }
+    }
+
+    #[inline]
+    fn pop_int<const CHECKED: bool>(&mut self, instruction: Instruction) -> Result<i64, VmError> {
+        match self.pop::<CHECKED>(instruction)? {
+            Value::Int(value) => Ok(value),
+            other if CHECKED => Err(self.type_mismatch(instruction, ValueType::Int, other)),
+            _ => unreachable!("verified program mistyped at {}", self.pc),
+        }
+    }
+
+    #[inline]
+    fn pop_bool<const CHECKED: bool>(&mut self, instruction: Instruction) -> Result<bool, VmError> {
+        match self.pop::<CHECKED>(instruction)? {
+            Value::Bool(value) => Ok(value),
+            other if CHECKED => Err(self.type_mismatch(instruction, ValueType::Bool, other)),
+            _ => unreachable!("verified program mistyped at {}", self.pc),
+        }
+    }
+
+    #[inline]
+    fn pop_u8<const CHECKED: bool>(&mut self, instruction: Instruction) -> Result<u8, VmError> {
+        let value = self.pop_int::<CHECKED>(instruction)?;
+        u8::try_from(value).map_err(|_| VmError::OutOfRange {
+            pc: self.pc,
+            instruction,
+            value,
+        })
+    }
+
+    #[inline]
+    fn int_binary<const CHECKED: bool>(
+        &mut self,
+        instruction: Instruction,
+        op: fn(i64, i64) -> Option<i64>,
+    ) -> Result<(), VmError> {
+        let rhs = self.pop_int::<CHECKED>(instruction)?;
+        let lhs = self.pop_int::<CHECKED>(instruction)?;
+        let result = op(lhs, rhs).ok_or(VmError::Overflow {
+            pc: self.pc,
+            instruction,
+        })?;
+        self.stack.push(Value::Int(result));
+        Ok(())
+    }
+
+    #[inline]
+    fn int_compare<const CHECKED: bool>(&mut self, instruction: Instruction, op: fn(i64, i64) -> bool) -> Result<(), VmError> {
+        let rhs = self.pop_int::<CHECKED>(instruction)?;
+        let lhs = self.pop_int::<CHECKED>(instruction)?;
+        self.stack.push(Value::Bool(op(lhs, rhs)));
+        Ok(())
+    }
+
+    #[inline]
+    fn jump_target<const CHECKED: bool>(&self, program: &Program, target: usize) -> Result<usize, VmError> {
+        if !CHECKED || target < program.len() {
+            Ok(target)
+        } else {
+            Err(VmError::InvalidJump { pc: self.pc, target })
+        }
+    }
+
+    #[inline]
+    fn type_mismatch(&self, instruction: Instruction, expected: ValueType, found: Value) -> VmError {
+        VmError::TypeMismatch {
+            pc: self.pc,
+            instruction,
+            expected,
+            found: found.kind(),
+        }
+    }
+}
+
+/// Outcome of executing a single instruction.
+enum Flow {
+    Continue,
+    Moved,
+    Halted,
+}
@@ -0,0 +1,120 @@
+mod common;
+
+use arc_vm::{binary, prelude::*, text};
+use common::{GOLDEN, code_lines};
+
+#[test]
+fn text_to_binary_to_text_is_identity() {
+    for (name, src, _) in GOLDEN {
+        let program = text::parse(src).unwrap();
+        let bytes = binary::encode(&program);
+        assert_eq!(binary::disassemble(&bytes).unwrap(), code_lines(src), "{name}");
+    }
+}
+
+#[test]
+fn binary_programs_evaluate_like_text() {
+    for (name, src, expected) in GOLDEN {
+        let program = binary::decode(&binary::encode(&text::parse(src).unwrap())).unwrap();
+        assert_eq!(Vm::new().run(&program).unwrap(), Some(expected), "{name}");
+    }
+}
+
+#[test]
+fn encodes_sum_compactly() {
+    let program = text::parse(GOLDEN[0].1).unwrap();
+    assert_eq!(
+        binary::encode(&program),
+        [
+            b'L', b'T', b'B', b'C', 1, // header
+            7, // instruction count
+            0x01, 4, // PUSH 2
+            0x01, 6,    // PUSH 3
+            0x10, // ADD
+            0x01, 8,    // PUSH 4
+            0x11, // NEG
+            0x10, // ADD
+            0x00, // HALT
+        ]
+    );
+}
+
+#[test]
+fn round_trips_extreme_immediates() {
+    let program = Program::new(vec![
+        Instruction::Push(i64::MIN),
+        Instruction::Push(i64::MAX),
+        Instruction::Push(-1),
+        Instruction::Push(0),
+        Instruction::Push(64),
+        Instruction::Push(-65),
+        Instruction::PushBool(true),
+        Instruction::PushBool(false),
+        Instruction::Halt,
+    ]);
+    assert_eq!(binary::decode(&binary::encode(&program)).unwrap(), program);
+}
+
+#[test]
+fn reports_decode_errors() {
+    let cases: [(&[u8], usize, DecodeErrorKind); 7] = [
+        (b"LTB", 3, DecodeErrorKind::UnexpectedEof),
+        (b"XTBC\x01\x00", 0, DecodeErrorKind::BadMagic),
+        (b"LTBC\x02\x00", 4, DecodeErrorKind::UnsupportedVersion(2)),
+        (b"LTBC\x01\x01\xff", 6, DecodeErrorKind::UnknownOpcode(0xff)),
+        (b"LTBC\x01\x01\x02\x07", 7, DecodeErrorKind::InvalidBool(7)),
+        (b"LTBC\x01\x01\x00\x00", 7, DecodeErrorKind::TrailingBytes),
+        (
+            b"LTBC\x01\x01\x01\xff\xff\xff\xff\xff\xff\xff\xff\xff\x7f",
+            7,
+            DecodeErrorKind::VarintOverflow,
+        ),
+    ];
+
+    for (bytes, offset, kind) in cases {
+        assert_eq!(binary::decode(bytes).unwrap_err(), DecodeError { offset, kind }, "{bytes:?}");
+    }
+}
+
+#[test]
+fn reports_truncated_operand() {
+    let mut bytes = binary::encode(&Program::new(vec![Instruction::Push(1 << 40), Instruction::Halt]));
+    bytes.truncate(9);
+    assert_eq!(binary::decode(&bytes).unwrap_err().kind, DecodeErrorKind::UnexpectedEof);
+}
+
+#[test]
+fn round_trips_calls_and_slots() {
+    let program = text::parse(include_str!("data/countdown.bc")).unwrap();
+    assert_eq!(binary::decode(&binary::encode(&program)).unwrap(), program);
+
+    let program = Program::new(vec![Instruction::StoreRegister(15), Instruction::Halt]);
+    let mut bytes = binary::encode(&program);
+    assert_eq!(bytes[6..], [0x53, 15, 0x00]);
+    bytes[7] = 16;
+    assert_eq!(
+        binary::decode(&bytes),
+        Err(DecodeError {
+            offset: 7,
+            kind: DecodeErrorKind::InvalidSlot(16),
+        })
+    );
+}
+
+#[test]
+fn round_trips_directions() {
+    let program = text::parse(include_str!("data/crowds.bc")).unwrap();
+    assert_eq!(binary::decode(&binary::encode(&program)).unwrap(), program);
+
+    let program = Program::new(vec![Instruction::Sense(Direction::AheadLeft), Instruction::Halt]);
+    let mut bytes = binary::encode(&program);
+    assert_eq!(bytes[6..], [0x46, 7, 0x00]);
+    bytes[7] = 8;
+    assert_eq!(
+        binary::decode(&bytes),
+        Err(DecodeError {
+            offset: 7,
+            kind: DecodeErrorKind::InvalidDirection(8),
+        })
+    );
+}
@@ -0,0 +1,373 @@
+mod common;
+
+use arc_vm::{prelude::*, text};
+use common::{Board, SIZE};
+
+#[test]
+fn langton_program_matches_reference() {
+    let program = text::parse(include_str!("data/langton.bc")).unwrap();
+    let mut vm = Vm::new();
+    let mut board = Board::new();
+    let mut reference = Board::new();
+
+    for _ in 0..2000 {
+        assert_eq!(vm.resume(&program, &mut board), Ok(Exit::Moved));
+        reference.langton_step();
+        assert_eq!(board.pos, reference.pos);
+        assert_eq!(board.heading, reference.heading);
+    }
+    assert_eq!(board.cells, reference.cells);
+}
+
+#[test]
+fn state_instructions_round_trip() {
+    let program = text::parse("STATE\nPUSH 3\nMUL\nPUSH 1\nSUB\nSET_STATE\nHALT\n").unwrap();
+    let mut board = Board::new();
+    board.state = 7;
+
+    assert_eq!(Vm::new().resume(&program, &mut board), Ok(Exit::Halted));
+    assert_eq!(board.state, 20);
+}
+
+#[test]
+fn halted_programs_stay_halted() {
+    let program = text::parse("MOVE\nHALT\n").unwrap();
+    let mut vm = Vm::new();
+    let mut board = Board::new();
+
+    assert_eq!(vm.resume(&program, &mut board), Ok(Exit::Moved));
+    assert_eq!(vm.resume(&program, &mut board), Ok(Exit::Halted));
+    assert_eq!(vm.resume(&program, &mut board), Ok(Exit::Halted));
+    assert_eq!(board.pos, (SIZE / 2, SIZE / 2 + 1));
+}
+
+#[test]
+fn budget_stops_runaway_programs() {
+    let program = text::parse("JUMP 0\nHALT\n").unwrap();
+    let mut vm = Vm::new();
+    assert_eq!(vm.resume_with_budget(&program, &mut Board::new(), 100), Ok(Exit::OutOfBudget));
+    assert_eq!(vm.pc(), 0);
+}
+
+#[test]
+fn comparisons_pick_a_branch() {
+    // max(3, 8)
+    let program = text::parse("PUSH 3\nPUSH 8\nLT\nJUMP_IF 6\nPUSH 3\nHALT\nPUSH 8\nHALT\n").unwrap();
+    assert_eq!(Vm::new().run(&program), Ok(Some(Value::Int(8))));
+
+    let program = text::parse("PUSH 2\nPUSH 2\nNE\nPUSH_BOOL false\nEQ\nHALT\n").unwrap();
+    assert_eq!(Vm::new().run(&program), Ok(Some(Value::Bool(true))));
+}
+
+#[test]
+fn loops_yield_at_each_move() {
+    // Count the state down to zero, moving once per iteration
+    let program =
+        text::parse("STATE\nPUSH 0\nGT\nJUMP_IF_NOT 10\nMOVE\nSTATE\nPUSH 1\nSUB\nSET_STATE\nJUMP 0\nHALT\n").unwrap();
+    let mut vm = Vm::new();
+    let mut board = Board::new();
+    board.state = 5;
+
+    let mut moves = 0;
+    while vm.resume(&program, &mut board).unwrap() == Exit::Moved {
+        moves += 1;
+    }
+    assert_eq!(moves, 5);
+    assert_eq!(board.state, 0);
+    assert!(vm.stack().is_empty());
+}
+
+#[test]
+fn reports_runtime_errors() {
+    let cases = [
+        (
+            "PUSH 256\nWRITE\nHALT",
+            VmError::OutOfRange {
+                pc: 1,
+                instruction: Instruction::Write,
+                value: 256,
+            },
+        ),
+        (
+            "PUSH -1\nSET_STATE\nHALT",
+            VmError::OutOfRange {
+                pc: 1,
+                instruction: Instruction::SetState,
+                value: -1,
+            },
+        ),
+        ("JUMP 9\nHALT", VmError::InvalidJump { pc: 0, target: 9 }),
+        (
+            "PUSH 1\nJUMP_IF 0\nHALT",
+            VmError::TypeMismatch {
+                pc: 1,
+                instruction: Instruction::JumpIf(0),
+                expected: ValueType::Bool,
+                found: ValueType::Int,
+            },
+        ),
+        (
+            "PUSH 1\nPUSH_BOOL true\nEQ\nHALT",
+            VmError::TypeMismatch {
+                pc: 2,
+                instruction: Instruction::Eq,
+                expected: ValueType::Int,
+                found: ValueType::Bool,
+            },
+        ),
+    ];
+
+    for (src, expected) in cases {
+        let program = text::parse(src).unwrap();
+        assert_eq!(Vm::new().resume(&program, &mut Board::new()), Err(expected), "{src:?}");
+    }
+}
+
+#[test]
+fn board_instructions_need_a_board() {
+    let program = text::parse("PUSH 1\nREAD\nHALT").unwrap();
+    assert_eq!(
+        Vm::new().run(&program),
+        Err(VmError::NoBoard {
+            pc: 1,
+            instruction: Instruction::Read,
+        })
+    );
+}
+
+#[test]
+fn reports_stack_and_arithmetic_errors() {
+    let cases = [
+        (
+            "PUSH 1\nADD\nHALT",
+            VmError::StackUnderflow {
+                pc: 1,
+                instruction: Instruction::Add,
+            },
+        ),
+        (
+            "PUSH 1\nPOP\nPOP\nHALT",
+            VmError::StackUnderflow {
+                pc: 2,
+                instruction: Instruction::Pop,
+            },
+        ),
+        ("PUSH 1\nPUSH 2\nADD", VmError::MissingHalt { pc: 3 }),
+        ("PUSH_BOOL false\nJUMP_IF 0", VmError::MissingHalt { pc: 2 }),
+        (
+            "PUSH 9223372036854775807\nPUSH 1\nADD\nHALT",
+            VmError::Overflow {
+                pc: 2,
+                instruction: Instruction::Add,
+            },
+        ),
+        (
+            "PUSH -9223372036854775808\nPUSH 2\nMUL\nHALT",
+            VmError::Overflow {
+                pc: 2,
+                instruction: Instruction::Mul,
+            },
+        ),
+        (
+            "PUSH 0\nPUSH -9223372036854775808\nNEG\nHALT",
+            VmError::Overflow {
+                pc: 2,
+                instruction: Instruction::Neg,
+            },
+        ),
+    ];
+
+    for (src, expected) in cases {
+        let program = text::parse(src).unwrap();
+        assert_eq!(Vm::new().run(&program), Err(expected), "{src:?}");
+    }
+}
+
+#[test]
+fn errors_leave_pc_at_the_failing_instruction() {
+    let program = text::parse("PUSH 9223372036854775807\nPUSH 1\nADD\nHALT").unwrap();
+    let mut vm = Vm::new();
+    assert!(vm.resume(&program, &mut Board::new()).is_err());
+    assert_eq!(vm.pc(), 2);
+    // The operands were already popped
+    assert!(vm.stack().is_empty());
+}
+
+#[test]
+fn verified_programs_run_like_checked_ones() {
+    let program = text::parse(include_str!("data/langton.bc")).unwrap();
+    let verified = program.clone().verify().unwrap();
+    let mut checked = (Vm::new(), Board::new());
+    let mut unchecked = (Vm::new(), Board::new());
+
+    for _ in 0..2000 {
+        assert_eq!(checked.0.resume(&program, &mut checked.1), Ok(Exit::Moved));
+        assert_eq!(
+            unchecked.0.resume_verified(&verified, &mut unchecked.1, u64::MAX),
+            Ok(Exit::Moved)
+        );
+        assert_eq!(checked.0.pc(), unchecked.0.pc());
+    }
+    assert_eq!(checked.1.cells, unchecked.1.cells);
+    assert_eq!(checked.1.pos, unchecked.1.pos);
+
+    // Runtime errors the verifier can't rule out are still reported
+    let verified = text::parse("PUSH 256\nWRITE\nHALT").unwrap().verify().unwrap();
+    assert_eq!(
+        Vm::new().resume_verified(&verified, &mut Board::new(), u64::MAX),
+        Err(VmError::OutOfRange {
+            pc: 1,
+            instruction: Instruction::Write,
+            value: 256,
+        })
+    );
+}
+
+#[test]
+fn stale_stacks_fall_back_to_checked_execution() {
+    // Left over from another program, the int on the stack doesn't match what the verifier expects
+    let mut vm = Vm::new();
+    vm.resume(&text::parse("PUSH 1\nMOVE\nHALT").unwrap(), &mut Board::new())
+        .unwrap();
+
+    let verified = text::parse("MOVE\nPUSH_BOOL true\nJUMP_IF 0\nHALT")
+        .unwrap()
+        .verify()
+        .unwrap();
+    assert_eq!(
+        vm.resume_verified(&verified, &mut Board::new(), u64::MAX),
+        Err(VmError::TypeMismatch {
+            pc: 2,
+            instruction: Instruction::JumpIf(0),
+            expected: ValueType::Bool,
+            found: ValueType::Int,
+        })
+    );
+}
+
+#[test]
+fn optimised_langton_program_matches_reference() {
+    let program = text::parse(include_str!("data/langton.bc")).unwrap().optimise(OptLevel::Full);
+    let mut vm = Vm::new();
+    let mut board = Board::new();
+    let mut reference = Board::new();
+
+    for _ in 0..2000 {
+        assert_eq!(vm.resume(&program, &mut board), Ok(Exit::Moved));
+        reference.langton_step();
+    }
+    assert_eq!(board.cells, reference.cells);
+    assert_eq!(board.pos, reference.pos);
+}
+
+#[test]
+fn subroutines_keep_their_own_locals() {
+    let program = text::parse(include_str!("data/countdown.bc")).unwrap();
+    let mut vm = Vm::new();
+    let mut board = Board::new();
+
+    for count in (1..=5).rev() {
+        assert_eq!(vm.resume(&program, &mut board), Ok(Exit::Moved));
+        // Yielding inside `paint` keeps its call and the main program's locals
+        assert_eq!(vm.frames().len(), 1);
+        assert_eq!(vm.locals()[0], count);
+        assert_eq!(vm.registers()[0], count);
+    }
+    assert_eq!(vm.resume(&program, &mut board), Ok(Exit::Halted));
+    assert!(vm.frames().is_empty());
+    assert_eq!(vm.locals(), [0; Vm::LOCALS]);
+
+    let mut painted: Vec<u8> = board.cells.iter().copied().filter(|&cell| cell != 0).collect();
+    painted.sort();
+    assert_eq!(painted, [1, 2, 3, 4, 5]);
+    assert_eq!(board.heading, 2);
+}
+
+#[test]
+fn registers_survive_a_reset() {
+    let program = text::parse("LOAD_REG 3\nPUSH 1\nADD\nDUP\nSTORE_REG 3\nHALT\n").unwrap();
+    let mut vm = Vm::new();
+    assert_eq!(vm.run(&program), Ok(Some(Value::Int(1))));
+    assert_eq!(vm.run(&program), Ok(Some(Value::Int(2))));
+
+    vm.clear_registers();
+    assert_eq!(vm.run(&program), Ok(Some(Value::Int(1))));
+}
+
+#[test]
+fn reports_call_errors() {
+    let program = text::parse("start: CALL start\nHALT\n").unwrap();
+    let mut vm = Vm::new();
+    assert_eq!(
+        vm.run(&program),
+        Err(VmError::LimitExceeded {
+            pc: 0,
+            limit: Limit::CallDepth,
+        })
+    );
+    assert_eq!(vm.frames().len(), Limits::default().call_depth);
+
+    let program = text::parse("PUSH 1\nRET\nHALT\n").unwrap();
+    assert_eq!(Vm::new().run(&program), Err(VmError::ReturnUnderflow { pc: 1 }));
+
+    let program = Program::new(vec![Instruction::LoadLocal(Vm::LOCALS as u8), Instruction::Halt]);
+    assert_eq!(
+        Vm::new().run(&program),
+        Err(VmError::InvalidSlot {
+            pc: 0,
+            instruction: Instruction::LoadLocal(Vm::LOCALS as u8),
+        })
+    );
+}
+
+#[test]
+fn senses_neighbours_relative_to_heading() {
+    let program = text::parse("SENSE AHEAD\nSENSE RIGHT\nSENSE BEHIND_LEFT\nSENSE AHEAD_LEFT\nHALT\n").unwrap();
+    let mut board = Board::new();
+    // Facing East in the bottom right corner, so ahead wraps to the left edge and right wraps to the top
+    board.pos = (SIZE - 1, 0);
+    board.heading = 1;
+    board.cells[0] = 1;
+    board.cells[((SIZE - 1) * SIZE + SIZE - 1) as usize] = 2;
+    board.cells[(SIZE + SIZE - 2) as usize] = 3;
+    board.cells[SIZE as usize] = 4;
+
+    let mut vm = Vm::new();
+    assert_eq!(vm.resume(&program, &mut board), Ok(Exit::Halted));
+    assert_eq!(vm.stack(), [1, 2, 3, 4].map(Value::Int));
+}
+
+#[test]
+fn counts_matching_neighbours() {
+    let program = text::parse("PUSH 1\nCOUNT_MOORE\nPUSH 1\nCOUNT_VON_NEUMANN\nPUSH 7\nCOUNT_MOORE\nHALT\n").unwrap();
+    let mut board = Board::new();
+    for direction in [
+        Direction::Ahead,
+        Direction::AheadRight,
+        Direction::Behind,
+        Direction::BehindLeft,
+    ] {
+        let (x, y) = board.neighbour(direction);
+        board.cells[(y * SIZE + x) as usize] = 1;
+    }
+    // The cell under the turmite isn't a neighbour
+    board.write(1);
+
+    let mut vm = Vm::new();
+    assert_eq!(vm.resume(&program, &mut board), Ok(Exit::Halted));
+    assert_eq!(vm.stack(), [4, 2, 0].map(Value::Int));
+}
+
+#[test]
+fn senses_adjacent_turmites() {
+    let program = text::parse("SENSE_TURMITE AHEAD\nSENSE_TURMITE BEHIND_RIGHT\nSENSE_TURMITE LEFT\nHALT\n").unwrap();
+    let mut board = Board::new();
+    board.turn(2);
+    board.others = vec![board.neighbour(Direction::BehindRight), (0, 0)];
+
+    let mut vm = Vm::new();
+    assert_eq!(vm.resume(&program, &mut board), Ok(Exit::Halted));
+    assert_eq!(vm.stack(), [false, true, false].map(Value::Bool));
+    // Facing South, behind is North and right is West
+    assert_eq!(board.others[0], (SIZE / 2 - 1, SIZE / 2 + 1));
+}
@@ -0,0 +1,109 @@
+#![allow(dead_code)] // Each test binary uses a different subset
+
+use arc_vm::prelude::*;
+
+// Output of `scripts/generate_bytecode.py` for each expression, with the value it evaluates to
+pub const GOLDEN: [(&str, &str, Value); 7] = [
+    ("sum", include_str!("../data/sum.bc"), Value::Int(1)),
+    ("constant", include_str!("../data/constant.bc"), Value::Int(42)),
+    ("bool", include_str!("../data/bool.bc"), Value::Bool(true)),
+    ("negated_sum", include_str!("../data/negated_sum.bc"), Value::Int(-3)),
+    ("double_negation", include_str!("../data/double_negation.bc"), Value::Int(7)),
+    ("long_sum", include_str!("../data/long_sum.bc"), Value::Int(15)),
+    ("large", include_str!("../data/large.bc"), Value::Int(i64::MIN)),
+];
+
+/// Instruction lines of a source file, without comments or blank lines.
+pub fn code_lines(src: &str) -> String {
+    src.lines()
+        .filter(|line| !line.is_empty() && !line.starts_with(';'))
+        .map(|line| format!("{line}\n"))
+        .collect()
+}
+
+pub const SIZE: i64 = 16;
+
+/// Step taken moving North, East, South and West.
+const DELTAS: [(i64, i64); 4] = [(0, 1), (1, 0), (0, -1), (-1, 0)];
+
+/// Toroidal test board with a single turmite.
+pub struct Board {
+    pub cells: Vec<u8>,
+    pub pos: (i64, i64),
+    pub heading: i64, // Quarter turns clockwise from North
+    pub state: u8,
+    /// Positions of other turmites on the board.
+    pub others: Vec<(i64, i64)>,
+}
+
+impl Board {
+    pub fn new() -> Self {
+        Self {
+            cells: vec![0; (SIZE * SIZE) as usize],
+            pos: (SIZE / 2, SIZE / 2),
+            heading: 0,
+            state: 0,
+            others: Vec::new(),
+        }
+    }
+
+    fn index(&self) -> usize {
+        (self.pos.1 * SIZE + self.pos.0) as usize
+    }
+
+    /// Position of the neighbour in `direction`, wrapping like `advance`.
+    pub fn neighbour(&self, direction: Direction) -> (i64, i64) {
+        let (forward, right) = direction.offset();
+        let (fx, fy) = DELTAS[self.heading as usize];
+        let (rx, ry) = DELTAS[(self.heading as usize + 1) % 4];
+        (
+            (self.pos.0 + fx * forward + rx * right).rem_euclid(SIZE),
+            (self.pos.1 + fy * forward + ry * right).rem_euclid(SIZE),
+        )
+    }
+
+    /// Reference Langton's ant step, written directly against the board.
+    pub fn langton_step(&mut self) {
+        let index = self.index();
+        self.turn(if self.cells[index] == 0 { 1 } else { -1 });
+        self.cells[index] ^= 1;
+        self.advance();
+    }
+}
+
+impl Host for Board {
+    fn read(&self) -> u8 {
+        self.cells[self.index()]
+    }
+
+    fn sense(&self, direction: Direction) -> u8 {
+        let (x, y) = self.neighbour(direction);
+        self.cells[(y * SIZE + x) as usize]
+    }
+
+    fn sense_turmite(&self, direction: Direction) -> bool {
+        self.others.contains(&self.neighbour(direction))
+    }
+
+    fn write(&mut self, value: u8) {
+        let index = self.index();
+        self.cells[index] = value;
+    }
+
+    fn turn(&mut self, quarter_turns: i64) {
+        self.heading = (self.heading + quarter_turns).rem_euclid(4);
+    }
+
+    fn advance(&mut self) {
+        let (dx, dy) = DELTAS[self.heading as usize];
+        self.pos = ((self.pos.0 + dx).rem_euclid(SIZE), (self.pos.1 + dy).rem_euclid(SIZE));
+    }
+
+    fn state(&self) -> u8 {
+        self.state
+    }
+
+    fn set_state(&mut self, state: u8) {
+        self.state = state;
+    }
+}
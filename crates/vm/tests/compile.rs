@@ -0,0 +1,106 @@
+mod common;
+
+use arc_vm::{prelude::*, text};
+use common::{Board, GOLDEN};
+
+fn executables(src: &str) -> [Executable; 2] {
+    let verified = text::parse(src).unwrap().verify().unwrap();
+    [Tier::Interpreted, Tier::Compiled].map(|tier| verified.clone().into_executable(tier))
+}
+
+/// Run every tier side by side with `budget` instructions per resume, checking they stay in lockstep.
+fn assert_identical(src: &str, budget: u64, resumes: usize) -> Board {
+    let [interpreted, compiled] = executables(src);
+    let mut expected = (Vm::new(), Board::new());
+    let mut actual = (Vm::new(), Board::new());
+
+    for _ in 0..resumes {
+        let exit = expected.0.resume_executable(&interpreted, &mut expected.1, budget);
+        assert_eq!(actual.0.resume_executable(&compiled, &mut actual.1, budget), exit);
+        assert_eq!(actual.0.pc(), expected.0.pc());
+        assert_eq!(actual.0.stack(), expected.0.stack());
+        assert_eq!(actual.0.frames(), expected.0.frames());
+        assert_eq!(actual.0.locals(), expected.0.locals());
+        assert_eq!(actual.0.registers(), expected.0.registers());
+        assert_eq!(
+            (actual.1.pos, actual.1.heading, actual.1.state),
+            (expected.1.pos, expected.1.heading, expected.1.state)
+        );
+        if exit.is_err() {
+            break;
+        }
+    }
+    assert_eq!(actual.1.cells, expected.1.cells);
+    actual.1
+}
+
+#[test]
+fn tiers_are_selectable() {
+    let [interpreted, compiled] = executables("HALT");
+    assert_eq!(interpreted.tier(), Tier::Interpreted);
+    assert_eq!(compiled.tier(), Tier::Compiled);
+}
+
+#[test]
+fn golden_programs_match_the_interpreter() {
+    for (name, src, expected) in GOLDEN {
+        for executable in executables(src) {
+            let mut vm = Vm::new();
+            assert_eq!(
+                vm.resume_executable(&executable, &mut Board::new(), u64::MAX),
+                Ok(Exit::Halted),
+                "{name}"
+            );
+            assert_eq!(vm.stack().last(), Some(&expected), "{name}");
+        }
+    }
+}
+
+#[test]
+fn langton_program_matches_the_interpreter() {
+    let mut reference = Board::new();
+    for _ in 0..2000 {
+        reference.langton_step();
+    }
+    let board = assert_identical(include_str!("data/langton.bc"), u64::MAX, 2000);
+    assert_eq!(board.cells, reference.cells);
+
+    // Pausing mid-step leaves both tiers at the same instruction
+    assert_identical(include_str!("data/langton.bc"), 3, 5000);
+}
+
+#[test]
+fn runtime_errors_match_the_interpreter() {
+    assert_identical("PUSH 9223372036854775807\nPUSH 1\nADD\nHALT", u64::MAX, 1);
+    assert_identical("PUSH -9223372036854775807\nPUSH 1\nSUB\nNEG\nHALT", u64::MAX, 1);
+    assert_identical("STATE\nPUSH 300\nADD\nWRITE\nHALT", u64::MAX, 1);
+    assert_identical("PUSH -1\nSET_STATE\nHALT", u64::MAX, 1);
+}
+
+#[test]
+fn loops_and_comparisons_match_the_interpreter() {
+    // Count the state down from 200 in threes, painting each value as it moves
+    let src =
+        "PUSH 200\nSET_STATE\nSTATE\nPUSH 3\nLT\nJUMP_IF 14\nSTATE\nDUP\nPUSH 3\nSUB\nSET_STATE\nWRITE\nMOVE\nJUMP 2\nHALT\n";
+    assert_identical(src, u64::MAX, 100);
+    assert_identical(src, 2, 1000);
+}
+
+#[test]
+fn calls_match_the_interpreter() {
+    let board = assert_identical(include_str!("data/countdown.bc"), u64::MAX, 10);
+    assert_eq!(board.heading, 2);
+    for budget in [1, 2, 5] {
+        assert_identical(include_str!("data/countdown.bc"), budget, 1000);
+    }
+    assert_identical("CALL f\nHALT\nf: CALL f\nRET\n", u64::MAX, 1);
+}
+
+#[test]
+fn sensing_matches_the_interpreter() {
+    let board = assert_identical(include_str!("data/crowds.bc"), u64::MAX, 3000);
+    assert!(board.cells.contains(&2));
+    for budget in [1, 3, 7] {
+        assert_identical(include_str!("data/crowds.bc"), budget, 5000);
+    }
+}
@@ -0,0 +1,146 @@
+mod common;
+
+use arc_vm::{prelude::*, text};
+use common::Board;
+
+fn executables(src: &str) -> [Executable; 2] {
+    let verified = text::parse(src).unwrap().verify().unwrap();
+    [Tier::Interpreted, Tier::Compiled].map(|tier| verified.clone().into_executable(tier))
+}
+
+#[test]
+fn counts_instructions() {
+    let program = text::parse("PUSH 1\nTURN\nMOVE\nJUMP 0\nHALT\n").unwrap();
+    let mut vm = Vm::new();
+    let mut board = Board::new();
+
+    assert_eq!(vm.resume_with_budget(&program, &mut board, 2), Ok(Exit::OutOfBudget));
+    assert_eq!((vm.executed(), vm.since_move()), (2, 2));
+    assert_eq!(vm.resume(&program, &mut board), Ok(Exit::Moved));
+    assert_eq!((vm.executed(), vm.since_move()), (3, 0));
+    assert_eq!(vm.resume(&program, &mut board), Ok(Exit::Moved));
+    assert_eq!((vm.executed(), vm.since_move()), (7, 0));
+}
+
+#[test]
+fn step_limit_spans_resumes() {
+    let limits = Limits {
+        step_instructions: 10,
+        ..Limits::default()
+    };
+    let program = text::parse("loop: JUMP loop\nHALT\n").unwrap();
+    let mut vm = Vm::with_limits(limits);
+    let mut board = Board::new();
+
+    assert_eq!(vm.resume_with_budget(&program, &mut board, 6), Ok(Exit::OutOfBudget));
+    assert_eq!(
+        vm.resume_with_budget(&program, &mut board, 6),
+        Err(VmError::LimitExceeded {
+            pc: 0,
+            limit: Limit::StepInstructions,
+        })
+    );
+    assert_eq!(vm.executed(), 10);
+
+    // Moving on the last allowed instruction is fine
+    let program = text::parse("PUSH 1\nPOP\nMOVE\nJUMP 0\nHALT\n").unwrap();
+    let mut vm = Vm::with_limits(Limits {
+        step_instructions: 3,
+        ..Limits::default()
+    });
+    assert_eq!(vm.resume(&program, &mut board), Ok(Exit::Moved));
+    assert_eq!(
+        vm.resume(&program, &mut board),
+        Err(VmError::LimitExceeded {
+            pc: 2,
+            limit: Limit::StepInstructions,
+        })
+    );
+}
+
+#[test]
+fn fuel_runs_out_across_moves() {
+    let limits = Limits {
+        fuel: 8,
+        ..Limits::default()
+    };
+    for executable in executables("MOVE\nPUSH 1\nTURN\nJUMP 0\nHALT\n") {
+        let mut vm = Vm::with_limits(limits);
+        let mut board = Board::new();
+        assert_eq!(vm.resume_executable(&executable, &mut board, u64::MAX), Ok(Exit::Moved));
+        assert_eq!(vm.resume_executable(&executable, &mut board, u64::MAX), Ok(Exit::Moved));
+        assert_eq!(
+            vm.resume_executable(&executable, &mut board, u64::MAX),
+            Err(VmError::LimitExceeded {
+                pc: 0,
+                limit: Limit::Fuel,
+            })
+        );
+        assert_eq!(vm.executed(), 8);
+
+        // Resetting restarts the program but not the fuel
+        vm.reset();
+        assert!(vm.resume_executable(&executable, &mut board, u64::MAX).is_err());
+    }
+}
+
+#[test]
+fn stack_depth_is_enforced_on_every_tier() {
+    let limits = Limits {
+        stack_depth: 3,
+        ..Limits::default()
+    };
+    let src = "PUSH 1\nPUSH 2\nPUSH 3\nPUSH 4\nPOP\nPOP\nPOP\nPOP\nHALT\n";
+    let expected = Err(VmError::LimitExceeded {
+        pc: 3,
+        limit: Limit::StackDepth,
+    });
+
+    let mut vm = Vm::with_limits(limits);
+    assert_eq!(vm.resume(&text::parse(src).unwrap(), &mut Board::new()), expected);
+    for executable in executables(src) {
+        let mut vm = Vm::with_limits(limits);
+        assert_eq!(vm.resume_executable(&executable, &mut Board::new(), u64::MAX), expected);
+        assert_eq!(vm.stack().len(), 4);
+    }
+
+    // Deep enough for the program, so it runs unchecked
+    for executable in executables(src) {
+        let mut vm = Vm::with_limits(Limits {
+            stack_depth: 4,
+            ..Limits::default()
+        });
+        assert_eq!(
+            vm.resume_executable(&executable, &mut Board::new(), u64::MAX),
+            Ok(Exit::Halted)
+        );
+    }
+}
+
+#[test]
+fn call_depth_is_configurable() {
+    let src = "CALL f\nHALT\nf: LOAD_REG 0\nPUSH 1\nADD\nSTORE_REG 0\nCALL f\nRET\n";
+    for executable in executables(src) {
+        let mut vm = Vm::with_limits(Limits {
+            call_depth: 5,
+            ..Limits::default()
+        });
+        assert_eq!(
+            vm.resume_executable(&executable, &mut Board::new(), u64::MAX),
+            Err(VmError::LimitExceeded {
+                pc: 6,
+                limit: Limit::CallDepth,
+            })
+        );
+        assert_eq!(vm.registers()[0], 5);
+    }
+}
+
+#[test]
+fn limit_errors_name_the_limit() {
+    let err = VmError::LimitExceeded {
+        pc: 4,
+        limit: Limit::StepInstructions,
+    };
+    assert_eq!(err.to_string(), "instructions per step limit exceeded at 4");
+}
@@ -0,0 +1,104 @@
+mod common;
+
+use arc_vm::{prelude::*, text};
+use common::GOLDEN;
+
+const LEVELS: [OptLevel; 3] = [OptLevel::None, OptLevel::Basic, OptLevel::Full];
+
+fn optimise(src: &str, level: OptLevel) -> String {
+    text::format(&text::parse(src).unwrap().optimise(level))
+}
+
+#[test]
+fn golden_programs_evaluate_identically_at_every_level() {
+    for (name, src, expected) in GOLDEN {
+        let program = text::parse(src).unwrap();
+        for level in LEVELS {
+            let optimised = program.optimise(level);
+            assert!(optimised.len() <= program.len(), "{name} at {level:?}");
+            assert_eq!(Vm::new().run(&optimised), Ok(Some(expected)), "{name} at {level:?}");
+        }
+    }
+}
+
+#[test]
+fn folds_golden_expressions_to_a_single_push() {
+    for (name, src, expected) in GOLDEN {
+        let optimised = text::parse(src).unwrap().optimise(OptLevel::Full);
+        let push = match expected {
+            Value::Int(value) => Instruction::Push(value),
+            Value::Bool(value) => Instruction::PushBool(value),
+        };
+        assert_eq!(optimised.instructions(), [push, Instruction::Halt], "{name}");
+    }
+}
+
+#[test]
+fn none_leaves_programs_alone() {
+    let src = "PUSH 7\nNEG\nNEG\nHALT\nPOP\n";
+    assert_eq!(optimise(src, OptLevel::None), src);
+}
+
+#[test]
+fn removes_double_negation() {
+    assert_eq!(
+        optimise("PUSH 7\nNEG\nNEG\nNEG\nHALT\n", OptLevel::Basic),
+        "PUSH 7\nNEG\nHALT\n"
+    );
+}
+
+#[test]
+fn removes_dead_code() {
+    assert_eq!(
+        optimise("PUSH 1\nHALT\nPUSH 2\nADD\nHALT\n", OptLevel::Basic),
+        "PUSH 1\nHALT\n"
+    );
+    // The jump over dead code becomes a jump to the next instruction, which goes too
+    assert_eq!(
+        optimise("JUMP 3\nPUSH 1\nPOP\nPUSH 2\nHALT\n", OptLevel::Basic),
+        "PUSH 2\nHALT\n"
+    );
+}
+
+#[test]
+fn folds_constant_branches() {
+    let src = "PUSH 3\nPUSH 8\nLT\nJUMP_IF 6\nPUSH 3\nHALT\nPUSH 8\nHALT\n";
+    assert_eq!(optimise(src, OptLevel::Full), "PUSH 8\nHALT\n");
+}
+
+#[test]
+fn keeps_overflow_at_runtime() {
+    let src = "PUSH 9223372036854775807\nPUSH 1\nADD\nHALT\n";
+    assert_eq!(optimise(src, OptLevel::Full), src);
+    assert!(matches!(
+        Vm::new().run(&text::parse(src).unwrap().optimise(OptLevel::Full)),
+        Err(VmError::Overflow { .. })
+    ));
+}
+
+#[test]
+fn does_not_fold_across_jump_targets() {
+    // Instruction 2 is reached with different stacks from the entry and from the loop
+    let src = "PUSH 3\nSET_STATE\nPUSH 1\nSTATE\nSUB\nSET_STATE\nSTATE\nPUSH 0\nEQ\nJUMP_IF_NOT 2\nHALT\n";
+    let optimised = text::parse(src).unwrap().optimise(OptLevel::Full);
+    assert_eq!(optimised, text::parse(src).unwrap());
+}
+
+#[test]
+fn renumbers_jumps() {
+    let src = "PUSH 2\nNEG\nNEG\nPOP\nREAD\nPUSH 0\nEQ\nJUMP_IF 11\nPUSH 1\nWRITE\nHALT\nPUSH 0\nWRITE\nHALT\n";
+    assert_eq!(
+        optimise(src, OptLevel::Full),
+        "READ\nPUSH 0\nEQ\nJUMP_IF 7\nPUSH 1\nWRITE\nHALT\nPUSH 0\nWRITE\nHALT\n"
+    );
+}
+
+#[test]
+fn renumbers_calls() {
+    let src = "PUSH 1\nPOP\nCALL 4\nHALT\nPUSH 2\nNEG\nNEG\nWRITE\nRET\n";
+    assert_eq!(
+        optimise(src, OptLevel::Basic),
+        "PUSH 1\nPOP\nCALL 4\nHALT\nPUSH 2\nWRITE\nRET\n"
+    );
+    assert_eq!(optimise(src, OptLevel::Full), "CALL 2\nHALT\nPUSH 2\nWRITE\nRET\n");
+}
@@ -0,0 +1,160 @@
+mod common;
+
+use arc_vm::{prelude::*, text};
+use common::{GOLDEN, code_lines};
+
+#[test]
+fn golden_programs_parse_and_evaluate() {
+    for (name, src, expected) in GOLDEN {
+        let program = text::parse(src).unwrap_or_else(|err| panic!("{name}: {err}"));
+        let result = Vm::new().run(&program).unwrap_or_else(|err| panic!("{name}: {err}"));
+        assert_eq!(result, Some(expected), "{name}");
+    }
+}
+
+#[test]
+fn golden_programs_print_back_unchanged() {
+    for (name, src, _) in GOLDEN {
+        let program = text::parse(src).unwrap();
+        assert_eq!(text::format(&program), code_lines(src), "{name}");
+    }
+}
+
+#[test]
+fn parses_sum_instructions() {
+    let program = text::parse(GOLDEN[0].1).unwrap();
+    assert_eq!(
+        program.instructions(),
+        [
+            Instruction::Push(2),
+            Instruction::Push(3),
+            Instruction::Add,
+            Instruction::Push(4),
+            Instruction::Neg,
+            Instruction::Add,
+            Instruction::Halt,
+        ]
+    );
+}
+
+#[test]
+fn accepts_comments_blank_lines_and_extra_whitespace() {
+    let program = text::parse(";; header\n\n  PUSH\t-5  ; trailing comment\n\nPUSH_BOOL false\n   \nHALT").unwrap();
+    assert_eq!(
+        program.instructions(),
+        [Instruction::Push(-5), Instruction::PushBool(false), Instruction::Halt]
+    );
+}
+
+#[test]
+fn reports_unknown_opcode_position() {
+    let err = text::parse("PUSH 1\n\n   FLY 2\nHALT\n").unwrap_err();
+    assert_eq!(
+        err,
+        ParseError {
+            line: 3,
+            column: 4,
+            kind: ParseErrorKind::UnknownOpcode("FLY".to_string()),
+        }
+    );
+}
+
+#[test]
+fn reports_bad_argument_positions() {
+    let cases = [
+        (
+            "PUSH x",
+            1,
+            6,
+            ParseErrorKind::InvalidArgument {
+                opcode: Opcode::Push,
+                argument: "x".to_string(),
+            },
+        ),
+        (
+            "PUSH 99999999999999999999",
+            1,
+            6,
+            ParseErrorKind::InvalidArgument {
+                opcode: Opcode::Push,
+                argument: "99999999999999999999".to_string(),
+            },
+        ),
+        (
+            "PUSH_BOOL True",
+            1,
+            11,
+            ParseErrorKind::InvalidArgument {
+                opcode: Opcode::PushBool,
+                argument: "True".to_string(),
+            },
+        ),
+        (
+            "SENSE FORWARD",
+            1,
+            7,
+            ParseErrorKind::InvalidArgument {
+                opcode: Opcode::Sense,
+                argument: "FORWARD".to_string(),
+            },
+        ),
+        ("HALT\n  PUSH", 2, 3, ParseErrorKind::MissingArgument { opcode: Opcode::Push }),
+        ("ADD 1", 1, 5, ParseErrorKind::UnexpectedArgument { opcode: Opcode::Add }),
+        ("PUSH 1 2", 1, 8, ParseErrorKind::UnexpectedArgument { opcode: Opcode::Push }),
+    ];
+
+    for (src, line, column, kind) in cases {
+        assert_eq!(text::parse(src).unwrap_err(), ParseError { line, column, kind }, "{src:?}");
+    }
+}
+
+#[test]
+fn error_message_includes_position() {
+    let err = text::parse("PUSH 1\nPUSH_BOOL maybe\n").unwrap_err();
+    assert_eq!(err.to_string(), "2:11: invalid argument `maybe` for PUSH_BOOL");
+}
+
+#[test]
+fn resolves_labels() {
+    let src = "start:\n  JUMP end ; forward\nmid: CALL start\nend: JUMP_IF_NOT mid\n  JUMP 1\n";
+    assert_eq!(
+        text::parse(src).unwrap().instructions(),
+        [
+            Instruction::Jump(2),
+            Instruction::Call(0),
+            Instruction::JumpIfNot(1),
+            Instruction::Jump(1),
+        ]
+    );
+}
+
+#[test]
+fn reports_label_and_slot_errors() {
+    let cases = [
+        ("JUMP nowhere", 1, 6, ParseErrorKind::UndefinedLabel("nowhere".to_string())),
+        ("a: HALT\n  a: HALT", 2, 3, ParseErrorKind::DuplicateLabel("a".to_string())),
+        ("1st: HALT", 1, 1, ParseErrorKind::InvalidLabel("1st".to_string())),
+        (
+            "LOAD_LOCAL 16",
+            1,
+            12,
+            ParseErrorKind::InvalidArgument {
+                opcode: Opcode::LoadLocal,
+                argument: "16".to_string(),
+            },
+        ),
+        (
+            "STORE_REG -1",
+            1,
+            11,
+            ParseErrorKind::InvalidArgument {
+                opcode: Opcode::StoreRegister,
+                argument: "-1".to_string(),
+            },
+        ),
+    ];
+
+    for (src, line, column, kind) in cases {
+        assert_eq!(text::parse(src).unwrap_err(), ParseError { line, column, kind }, "{src:?}");
+    }
+}
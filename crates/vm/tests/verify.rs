@@ -0,0 +1,165 @@
+mod common;
+
+use arc_vm::{prelude::*, text};
+use common::GOLDEN;
+
+fn verify_src(src: &str) -> Result<VerifiedProgram, VerifyError> {
+    text::parse(src).unwrap().verify()
+}
+
+fn error_kind(src: &str) -> (usize, VerifyErrorKind) {
+    let err = verify_src(src).unwrap_err();
+    (err.pc, err.kind)
+}
+
+#[test]
+fn golden_programs_verify() {
+    for (name, src, expected) in GOLDEN {
+        let verified = verify_src(src).unwrap_or_else(|err| panic!("{name}: {err}"));
+        let top = verified
+            .stack_types(verified.len() - 1)
+            .and_then(|types| types.last().copied());
+        assert_eq!(top, Some(expected.kind()), "{name}");
+    }
+}
+
+#[test]
+fn tracks_stack_depth_and_types() {
+    let verified = verify_src("PUSH 1\nPUSH 2\nPUSH 3\nADD\nLT\nHALT\n").unwrap();
+    assert_eq!(verified.max_depth(), 3);
+    assert_eq!(verified.stack_types(0), Some(&[][..]));
+    assert_eq!(verified.stack_types(4), Some(&[ValueType::Int, ValueType::Int][..]));
+    assert_eq!(verified.stack_types(5), Some(&[ValueType::Bool][..]));
+}
+
+#[test]
+fn follows_jumps() {
+    // Both branches leave one int before rejoining, and the skipped instruction is unreachable
+    let src = "PUSH_BOOL true\nJUMP_IF 5\nPUSH 1\nJUMP 6\nPUSH_BOOL false\nPUSH 2\nHALT\n";
+    let verified = verify_src(src).unwrap();
+    assert_eq!(verified.stack_types(4), None);
+    assert_eq!(verified.stack_types(6), Some(&[ValueType::Int][..]));
+
+    assert!(verify_src(include_str!("data/langton.bc")).is_ok());
+}
+
+#[test]
+fn rejects_underflow() {
+    assert_eq!(
+        error_kind("PUSH 1\nADD\nHALT\n"),
+        (
+            1,
+            VerifyErrorKind::StackUnderflow {
+                instruction: Instruction::Add
+            }
+        )
+    );
+}
+
+#[test]
+fn rejects_mixed_types() {
+    assert_eq!(
+        error_kind("PUSH 1\nPUSH_BOOL true\nADD\nHALT\n"),
+        (
+            2,
+            VerifyErrorKind::TypeMismatch {
+                instruction: Instruction::Add,
+                expected: ValueType::Int,
+                found: ValueType::Bool,
+            }
+        )
+    );
+    assert_eq!(
+        error_kind("PUSH 1\nPUSH_BOOL true\nEQ\nHALT\n"),
+        (
+            2,
+            VerifyErrorKind::TypeMismatch {
+                instruction: Instruction::Eq,
+                expected: ValueType::Int,
+                found: ValueType::Bool,
+            }
+        )
+    );
+    assert!(matches!(
+        error_kind("PUSH 1\nJUMP_IF 0\nHALT\n"),
+        (1, VerifyErrorKind::TypeMismatch { .. })
+    ));
+}
+
+#[test]
+fn rejects_missing_halt() {
+    assert_eq!(error_kind(""), (0, VerifyErrorKind::MissingHalt));
+    assert_eq!(error_kind("PUSH 1\n"), (1, VerifyErrorKind::MissingHalt));
+    // Only the fall-through branch runs off the end
+    assert_eq!(
+        error_kind("PUSH_BOOL true\nJUMP_IF 3\nHALT\nPUSH 1\n"),
+        (4, VerifyErrorKind::MissingHalt)
+    );
+}
+
+#[test]
+fn rejects_invalid_jumps() {
+    assert_eq!(error_kind("JUMP 5\nHALT\n"), (0, VerifyErrorKind::InvalidJump { target: 5 }));
+}
+
+#[test]
+fn rejects_inconsistent_stacks() {
+    // A loop that grows the stack on every iteration
+    let (pc, kind) = error_kind("PUSH 1\nJUMP 0\n");
+    assert_eq!(pc, 0);
+    assert!(matches!(kind, VerifyErrorKind::StackMismatch { .. }));
+
+    // Branches that rejoin with different types
+    let (pc, kind) = error_kind("PUSH_BOOL true\nJUMP_IF 4\nPUSH 1\nJUMP 5\nPUSH_BOOL false\nHALT\n");
+    assert_eq!(pc, 5);
+    assert!(matches!(kind, VerifyErrorKind::StackMismatch { .. }));
+}
+
+#[test]
+fn follows_calls_to_their_return() {
+    let verified = verify_src(include_str!("data/countdown.bc")).unwrap();
+    let paint = text::parse(include_str!("data/countdown.bc")).unwrap().instructions()[7];
+    let Instruction::Call(paint) = paint else {
+        panic!("expected CALL, found {paint}");
+    };
+    assert_eq!(verified.subroutine(paint), Some(paint));
+    assert_eq!(verified.subroutine(paint + 1), Some(paint));
+    assert_eq!(verified.subroutine(0), None);
+
+    // `is_even` swaps an int for a bool
+    assert_eq!(verified.stack_types(paint + 3), Some(&[ValueType::Bool][..]));
+}
+
+#[test]
+fn rejects_misused_subroutines() {
+    assert_eq!(error_kind("PUSH 1\nRET\n"), (1, VerifyErrorKind::ReturnFromMain));
+
+    // Falling through into a subroutine body
+    assert_eq!(
+        error_kind("CALL 3\nPUSH 1\nPOP\nRET\n"),
+        (
+            3,
+            VerifyErrorKind::SharedCode {
+                expected: Some(3),
+                found: None,
+            }
+        )
+    );
+
+    // Called with different stacks
+    let (pc, kind) = error_kind("CALL f\nPUSH 1\nCALL f\nHALT\nf: RET\n");
+    assert_eq!(pc, 4);
+    assert!(matches!(kind, VerifyErrorKind::StackMismatch { .. }));
+
+    // Returning with different stacks
+    let (_, kind) = error_kind("CALL f\nHALT\nf: READ\nPUSH 0\nEQ\nJUMP_IF g\nRET\ng: PUSH 1\nRET\n");
+    assert!(matches!(kind, VerifyErrorKind::StackMismatch { .. }));
+
+    let program = Program::new(vec![Instruction::StoreLocal(200), Instruction::Halt]);
+    assert_eq!(
+        program.verify().unwrap_err().kind,
+        VerifyErrorKind::InvalidSlot {
+            instruction: Instruction::StoreLocal(200),
+        }
+    );
+}